@@ -0,0 +1,100 @@
+//! Forward-kinematics playback without inverse-kinematics.
+//!
+//! Keyframe postures are interpolated with `PostureInterpolator`
+//! and sampled into a `Posture` each frame.
+
+use {
+    macroquad::{
+        camera::{set_camera, Camera3D},
+        color::{Color, DARKGRAY, GOLD, LIME, MAROON, ORANGE, PINK, YELLOW},
+        input::{is_key_pressed, KeyCode},
+        models::draw_line_3d,
+        time::get_frame_time,
+        window::{clear_background, next_frame},
+    },
+    na::{Isometry3, Point3, UnitQuaternion, Vector3},
+    skelly::{Posture, PostureInterpolator, Skelly},
+};
+
+/// Seconds between consecutive keyframes.
+const KEY_DURATION: f32 = 0.75;
+
+/// Bend angles of the spine, shoulder and elbow at each keyframe.
+const KEYS: [[f32; 3]; 4] = [
+    [0.0, 0.0, 0.0],
+    [0.3, 1.2, -0.8],
+    [-0.2, 0.4, -1.6],
+    [-0.4, -0.6, -0.4],
+];
+
+#[macroquad::main("fk-playback")]
+async fn main() {
+    let mut skelly = Skelly::<f32, Color>::new();
+    let root = skelly.add_root_with(Point3::origin(), GOLD);
+    let spine = skelly.attach_with(Vector3::z(), root, MAROON);
+    let neck = skelly.attach_with(Vector3::z(), spine, PINK);
+    let shoulder = skelly.attach_with(Vector3::x(), neck, ORANGE);
+    let elbow = skelly.attach_with(Vector3::x(), shoulder, LIME);
+    let _palm = skelly.attach_with(Vector3::x(), elbow, YELLOW);
+
+    let key_posture = |index: usize| {
+        let [spine_angle, shoulder_angle, elbow_angle] = KEYS[index % KEYS.len()];
+        let mut posture = Posture::new(&skelly);
+        posture.set_orientation(
+            spine,
+            UnitQuaternion::from_euler_angles(spine_angle, 0.0, 0.0),
+        );
+        posture.set_orientation(
+            shoulder,
+            UnitQuaternion::from_euler_angles(0.0, shoulder_angle, 0.0),
+        );
+        posture.set_orientation(
+            elbow,
+            UnitQuaternion::from_euler_angles(0.0, elbow_angle, 0.0),
+        );
+        posture
+    };
+
+    let mut interpolator = PostureInterpolator::new(key_posture(0), key_posture(1));
+    let mut posture = Posture::new(&skelly);
+    let mut globals = vec![Isometry3::identity(); skelly.len()];
+
+    let mut key = 0;
+    let mut time = 0.0;
+
+    let mut camera = Camera3D::default();
+    camera.position.y += 5.0;
+
+    loop {
+        next_frame().await;
+
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+
+        time += get_frame_time();
+        while time >= KEY_DURATION {
+            time -= KEY_DURATION;
+            key += 1;
+            interpolator.push(&key_posture(key + 1));
+        }
+
+        interpolator.sample(time / KEY_DURATION, &mut posture);
+        posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+
+        set_camera(&camera);
+        clear_background(DARKGRAY);
+
+        for index in 0..skelly.len() {
+            if let Some(parent) = skelly.get_parent(index) {
+                let start = &globals[parent].translation.vector;
+                let end = &globals[index].translation.vector;
+                draw_line_3d(
+                    macroquad::math::Vec3::new(start.x, start.y, start.z),
+                    macroquad::math::Vec3::new(end.x, end.y, end.z),
+                    *skelly.get_userdata(index),
+                );
+            }
+        }
+    }
+}
@@ -32,13 +32,15 @@
 //! posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
 //!
 
+pub mod chain;
+pub mod constraint;
 pub mod fabrik;
 pub mod frik;
 pub mod rotor;
 
 use {
     crate::skelly::{Posture, Skelly},
-    na::Scalar,
+    na::{Point3, RealField, Scalar, Unit, UnitQuaternion, Vector3},
 };
 
 /// Variants of results for `IkSolver::solve_step` method.
@@ -54,12 +56,638 @@ pub enum StepResult {
     Infeasible,
 }
 
+/// Order in which solver processes multiple goals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SolveMode {
+    /// All goals are processed on each step.
+    /// Corrections are averaged at joints shared by multiple goals.
+    #[default]
+    Simultaneous,
+
+    /// One goal is processed on each step, cycling through all goals.
+    Sequential,
+
+    /// All goals are processed on each step, from roots toward tips.
+    ///
+    /// Effector of a position goal serves as a sub-base for goals further down its chain.
+    /// Corrections for those goals do not propagate above the sub-base
+    /// while they can be satisfied from it,
+    /// so goals along the same chain, like elbow hint and palm target, do not fight.\
+    /// Sub-base is soft: when further goal can not be reached from it,
+    /// half of the remaining correction propagates above the sub-base,
+    /// so that both goals are approached.\
+    /// Goals closer to the root are processed first, then further goals
+    /// are solved from the updated sub-base.
+    Chained,
+}
+
+/// Handling of root bones by solvers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RootMode {
+    /// Root bones stay in place and only rotate.
+    #[default]
+    Pinned,
+
+    /// Root bones translate toward goals that chains cannot reach by rotation alone.
+    Floating,
+}
+
+/// Norm combining errors of multiple goals into the total error.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ErrorNorm {
+    /// Sum of all goal errors.
+    #[default]
+    Sum,
+
+    /// Largest of goal errors.
+    /// Solved only when every goal is within solver's error.
+    Max,
+
+    /// Root of mean squared goal errors.
+    RootMeanSquare,
+}
+
+impl ErrorNorm {
+    /// Adds goal `error` to accumulated `total`.
+    pub(crate) fn accumulate<T>(self, total: T, error: T) -> T
+    where
+        T: RealField + Copy,
+    {
+        match self {
+            ErrorNorm::Sum => total + error,
+            ErrorNorm::Max => total.max(error),
+            ErrorNorm::RootMeanSquare => total + error * error,
+        }
+    }
+
+    /// Returns total error from accumulated `total` of `count` goal errors.
+    pub(crate) fn finish<T>(self, total: T, count: usize) -> T
+    where
+        T: RealField + Copy,
+    {
+        match self {
+            ErrorNorm::RootMeanSquare if count > 0 => (total / na::convert(count as f64)).sqrt(),
+            _ => total,
+        }
+    }
+}
+
+/// Order of rotations for Euler angles.
+///
+/// Angles are specified in the order of axes in variant name
+/// and applied in that order about fixed global axes.\
+/// `EulerOrder::XYZ` matches [`UnitQuaternion::from_euler_angles`].
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::ik::EulerOrder, na::{UnitQuaternion, Vector3}};
+/// let angles = [0.1f32, 0.2, 0.3];
+///
+/// let xyz = EulerOrder::XYZ.rotation(angles);
+/// assert!(xyz.angle_to(&UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3)) < 1e-6);
+///
+/// let zyx = EulerOrder::ZYX.rotation(angles);
+/// let expected = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.3)
+///     * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.2)
+///     * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.1);
+/// assert!(zyx.angle_to(&expected) < 1e-6);
+///
+/// assert!(xyz.angle_to(&zyx) > 0.01);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    /// Returns rotation built from `angles` in this order.
+    pub fn rotation<T>(self, angles: [T; 3]) -> UnitQuaternion<T>
+    where
+        T: RealField + Copy,
+    {
+        let axes = match self {
+            EulerOrder::XYZ => [Vector3::x_axis(), Vector3::y_axis(), Vector3::z_axis()],
+            EulerOrder::XZY => [Vector3::x_axis(), Vector3::z_axis(), Vector3::y_axis()],
+            EulerOrder::YXZ => [Vector3::y_axis(), Vector3::x_axis(), Vector3::z_axis()],
+            EulerOrder::YZX => [Vector3::y_axis(), Vector3::z_axis(), Vector3::x_axis()],
+            EulerOrder::ZXY => [Vector3::z_axis(), Vector3::x_axis(), Vector3::y_axis()],
+            EulerOrder::ZYX => [Vector3::z_axis(), Vector3::y_axis(), Vector3::x_axis()],
+        };
+
+        axes.iter()
+            .zip(angles)
+            .fold(UnitQuaternion::identity(), |rotation, (axis, angle)| {
+                UnitQuaternion::from_axis_angle(axis, angle) * rotation
+            })
+    }
+}
+
 /// Trait for ik solvers.
 /// Using this common interface user may replace implementation easily.
+///
+/// Solvers are `Send` and `Sync` when `T` is,
+/// so a solver configured on one thread may be used on another.
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, ik::{fabrik::FabrikSolver, frik::FrikSolver, rotor::RotorSolver, chain::Chain}}, na::RealField};
+/// fn assert_send<T: Send>() {}
+/// fn assert_sync<T: Sync>() {}
+///
+/// fn assert_thread_safe<T: RealField + Copy>() {
+///     assert_send::<FabrikSolver<T>>();
+///     assert_sync::<FabrikSolver<T>>();
+///     assert_send::<FrikSolver<T>>();
+///     assert_sync::<FrikSolver<T>>();
+///     assert_send::<RotorSolver<T>>();
+///     assert_sync::<RotorSolver<T>>();
+///     assert_send::<Chain<T>>();
+///     assert_sync::<Chain<T>>();
+///     assert_send::<Skelly<T>>();
+///     assert_sync::<Skelly<T>>();
+///     assert_send::<Posture<T>>();
+///     assert_sync::<Posture<T>>();
+/// }
+///
+/// assert_thread_safe::<f32>();
+/// assert_thread_safe::<f64>();
+/// ```
 pub trait IkSolver<T: Scalar> {
     /// Returns new solver with maximum tolerable error.
     fn new(error: T) -> Self;
 
     /// Performs one step toward solution.
     fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult;
+
+    /// Returns total error of goals measured on the last step.
+    ///
+    /// Returns `None` if solver does not track the error.
+    fn last_error(&self) -> Option<T> {
+        None
+    }
+
+    /// Returns minimal error decrease per step for [`IkSolver::solve`] to keep going.
+    ///
+    /// Returns `None` if solving should not stop early.
+    fn min_improvement(&self) -> Option<T> {
+        None
+    }
+
+    /// Performs up to `max_steps` steps toward solution.
+    ///
+    /// Stops as soon as goals are solved.\
+    /// If minimal improvement is set, also stops when error changes by less than it between steps,
+    /// restoring the best posture found and returning [`StepResult::Unsolved`].\
+    /// Steps that return [`StepResult::Infeasible`] do not stop solving,
+    /// as the solver still moves effectors as close to the goals as it can.\
+    /// Returns result of the last performed step.
+    fn solve<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let min_improvement = self.min_improvement();
+        solve_steps(
+            skelly,
+            posture,
+            max_steps,
+            min_improvement,
+            |posture| {
+                let result = self.solve_step(skelly, posture);
+                (result, self.last_error())
+            },
+            |_, _| true,
+        )
+    }
+}
+
+/// Default number of steps performed by `update` method of solvers.
+pub const DEFAULT_UPDATE_STEPS: usize = 16;
+
+/// Performs up to `max_steps` calls to `step`
+/// calling `f` with step index and total error returned by each call.
+///
+/// Stops when `step` returns [`StepResult::Solved`] or `f` returns `false`.\
+/// If `min_improvement` is set, also stops when total error returned by `step`
+/// changes by less than `min_improvement`, restores the posture with the least error
+/// and returns [`StepResult::Unsolved`].\
+/// Error returned by `step` must be measured on the posture it was given.\
+/// Otherwise returns result of the last performed step.
+pub(crate) fn solve_steps<T, D>(
+    skelly: &Skelly<T, D>,
+    posture: &mut Posture<T>,
+    max_steps: usize,
+    min_improvement: Option<T>,
+    mut step: impl FnMut(&mut Posture<T>) -> (StepResult, Option<T>),
+    mut f: impl FnMut(usize, T) -> bool,
+) -> StepResult
+where
+    T: RealField + Copy,
+{
+    // Posture before the current step and the best posture found so far.
+    let mut before = None;
+    let mut best = None;
+    let mut best_error = None;
+
+    let mut result = StepResult::Unsolved;
+    let mut last_error = None;
+    for index in 0..max_steps {
+        if min_improvement.is_some() {
+            before
+                .get_or_insert_with(|| Posture::new(skelly))
+                .copy_from(posture);
+        }
+
+        let (step_result, error) = step(posture);
+        result = step_result;
+        if !f(index, error.unwrap_or_else(T::zero)) || result == StepResult::Solved {
+            break;
+        }
+
+        if let (Some(min_improvement), Some(error)) = (min_improvement, error) {
+            if best_error.map_or(true, |best_error| error < best_error) {
+                best_error = Some(error);
+                std::mem::swap(&mut best, &mut before);
+            }
+
+            if let Some(last_error) = last_error {
+                let improvement: T = last_error - error;
+                if improvement.abs() < min_improvement {
+                    if let Some(best) = &best {
+                        posture.copy_from(best);
+                    }
+                    return StepResult::Unsolved;
+                }
+            }
+        }
+        last_error = error;
+    }
+    result
+}
+
+/// Returns `position` moved toward `target` by at most `speed * dt`.
+pub(crate) fn ease_toward<T>(position: Point3<T>, target: Point3<T>, speed: T, dt: T) -> Point3<T>
+where
+    T: RealField + Copy,
+{
+    let offset = target - position;
+    let distance = offset.magnitude();
+    let step = speed * dt;
+
+    if distance > step {
+        position + offset * (step / distance)
+    } else {
+        target
+    }
+}
+
+/// Postures kept by solvers between steps solved in place.
+pub(crate) struct InPlace<T: Scalar> {
+    /// Skelly pose before solving in place started.
+    rest: Posture<T>,
+
+    /// Skelly pose written by the last step.
+    working: Posture<T>,
+
+    /// Skelly revision after the last step was written.
+    revision: u64,
+}
+
+/// Performs `step` posing the `skelly` itself.
+///
+/// Skelly is restored to its rest pose for the `step`,
+/// so that constraints are applied relative to it,
+/// and then assumes pose produced by the `step`.\
+/// Rest pose is recaptured when the skelly was changed since the previous call.
+pub(crate) fn step_in_place<T, D>(
+    in_place: &mut Option<InPlace<T>>,
+    skelly: &mut Skelly<T, D>,
+    step: impl FnOnce(&Skelly<T, D>, &mut Posture<T>) -> StepResult,
+) -> StepResult
+where
+    T: RealField + Copy,
+{
+    let state = in_place.get_or_insert_with(|| InPlace {
+        rest: Posture::new(skelly),
+        working: Posture::new(skelly),
+        revision: skelly.revision(),
+    });
+
+    if state.revision != skelly.revision() {
+        state.rest.reset_to(skelly);
+        state.working.reset_to(skelly);
+    }
+
+    skelly.assume_posture(&state.rest);
+    let result = step(skelly, &mut state.working);
+    skelly.assume_posture(&state.working);
+    state.revision = skelly.revision();
+    result
+}
+
+/// Solver which accepts position goals.
+pub trait GoalSolver<T: Scalar> {
+    /// Sets position goal for the `bone`, replacing previous one.
+    ///
+    /// Returns `false` if `position` is not finite and was rejected.
+    fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool;
+}
+
+/// Solves single goal placing the `tip` bone at the `target`
+/// with solver `S` and returns resulting posture.
+///
+/// Solver is created with `epsilon` as maximum tolerable error
+/// and performs up to `max_steps` steps.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, ik::{solve_chain, fabrik::FabrikSolver, frik::FrikSolver, rotor::RotorSolver}}, na::{Isometry3, Point3, Vector3}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let shoulder = skelly.attach(Vector3::x(), root);
+/// let elbow = skelly.attach(Vector3::x(), shoulder);
+/// let palm = skelly.attach(Vector3::x(), elbow);
+///
+/// let target = Point3::new(0.5, 1.5, 1.0);
+/// let postures = [
+///     solve_chain::<FabrikSolver<f32>, _, _>(&skelly, palm, target, 0.001, 100),
+///     solve_chain::<FrikSolver<f32>, _, _>(&skelly, palm, target, 0.001, 100),
+///     solve_chain::<RotorSolver<f32>, _, _>(&skelly, palm, target, 0.001, 100),
+/// ];
+///
+/// for posture in &postures {
+///     let mut globals = [Isometry3::identity(); 4];
+///     posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+///     assert!((Point3::from(globals[palm].translation.vector) - target).magnitude() < 0.001);
+/// }
+/// ```
+pub fn solve_chain<S, T, D>(
+    skelly: &Skelly<T, D>,
+    tip: usize,
+    target: Point3<T>,
+    epsilon: T,
+    max_steps: usize,
+) -> Posture<T>
+where
+    S: IkSolver<T> + GoalSolver<T>,
+    T: RealField + Copy,
+{
+    let mut solver = S::new(epsilon);
+    solver.set_position_goal(tip, target);
+
+    let mut posture = Posture::new(skelly);
+    solver.solve(skelly, &mut posture, max_steps);
+    posture
+}
+
+/// Analytically solves two-bone chain ending with the `tip` bone,
+/// placing the `tip` at the `target`.
+///
+/// Parent of the `tip` is the middle joint (e.g. knee)
+/// and its parent is the upper joint (e.g. hip).
+/// Rotations of the upper and middle joints in the `posture` are adjusted.\
+/// Reach of the chain is capped at `(len1 + len2) * (1 - bend_bias)`,
+/// so the middle joint keeps slight bend even when the `target` is out of reach.
+/// `bend_bias` must be in range `0 <= bend_bias < 1`,
+/// zero allows the chain to fully stretch.\
+/// When the chain is straight it is bent toward the `target`,
+/// or in arbitrary direction when the `target` is in line with the chain.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, ik::solve_two_bone_soft}, na::{Isometry3, Point3, Vector3}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::new(0.0, 0.0, 2.0));
+/// let hip = skelly.attach(Vector3::zeros(), root);
+/// let knee = skelly.attach(-Vector3::z(), hip);
+/// let foot = skelly.attach(-Vector3::z(), knee);
+///
+/// let mut globals = [Isometry3::identity(); 4];
+/// let mut posture = Posture::new(&skelly);
+///
+/// // Reachable target.
+/// let target = Point3::new(0.5, 0.5, 0.5);
+/// solve_two_bone_soft(&skelly, &mut posture, foot, target, 0.0);
+/// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+/// assert!((Point3::from(globals[foot].translation.vector) - target).magnitude() < 1e-5);
+///
+/// // Target at full stretch leaves the knee bent.
+/// let mut posture = Posture::new(&skelly);
+/// solve_two_bone_soft(&skelly, &mut posture, foot, Point3::origin(), 0.05);
+/// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+///
+/// let thigh = globals[knee].translation.vector - globals[hip].translation.vector;
+/// let shin = globals[foot].translation.vector - globals[knee].translation.vector;
+/// assert!(thigh.angle(&shin) > 0.1);
+/// assert!((globals[foot].translation.vector.z - 0.1).abs() < 1e-5);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the `tip` has no grandparent
+/// or `posture` is not compatible with the `skelly`.\
+/// In debug builds also panics if `bend_bias` is out of range.
+#[track_caller]
+pub fn solve_two_bone_soft<T, D>(
+    skelly: &Skelly<T, D>,
+    posture: &mut Posture<T>,
+    tip: usize,
+    target: Point3<T>,
+    bend_bias: T,
+) where
+    T: RealField + Copy,
+{
+    assert!(
+        posture.is_compatible(skelly),
+        "Posture is not compatible with the skelly"
+    );
+    debug_assert!(
+        bend_bias >= T::zero() && bend_bias < T::one(),
+        "Bend bias {} is out of range [0, 1)",
+        bend_bias,
+    );
+
+    let middle = skelly.get_parent(tip).expect("Tip bone must have a parent");
+    let upper = skelly
+        .get_parent(middle)
+        .expect("Tip bone must have a grandparent");
+
+    // Global isometries of the upper, middle and tip joints.
+    let chain_globals = |posture: &mut Posture<T>| {
+        let mut upper_global = *posture.get_isometry(upper);
+        for ancestor in skelly.iter_chain(upper) {
+            upper_global = posture.get_isometry(ancestor) * upper_global;
+        }
+        let middle_global = upper_global * posture.get_isometry(middle);
+        let tip_global = middle_global * posture.get_isometry(tip);
+        [upper_global, middle_global, tip_global]
+    };
+
+    let [upper_global, middle_global, tip_global] = chain_globals(posture);
+    let a = upper_global.translation.vector;
+    let b = middle_global.translation.vector;
+    let c = tip_global.translation.vector;
+    let t = target.coords;
+
+    let upper_length = (b - a).magnitude();
+    let lower_length = (c - b).magnitude();
+    let epsilon = T::default_epsilon();
+    if upper_length <= epsilon || lower_length <= epsilon {
+        return;
+    }
+
+    let max_reach = (upper_length + lower_length) * (T::one() - bend_bias);
+    let reach = (t - a).magnitude().min(max_reach).max(epsilon);
+
+    let clamped_angle = |cos: T| cos.max(-T::one()).min(T::one()).acos();
+    let angle = |lhs: &Vector3<T>, rhs: &Vector3<T>| match (
+        lhs.try_normalize(epsilon),
+        rhs.try_normalize(epsilon),
+    ) {
+        (Some(lhs), Some(rhs)) => clamped_angle(lhs.dot(&rhs)),
+        _ => T::zero(),
+    };
+
+    let two = T::one() + T::one();
+
+    // Current and required angles at the upper and middle joints.
+    let upper_angle = angle(&(c - a), &(b - a));
+    let middle_angle = angle(&(a - b), &(c - b));
+
+    let upper_angle_required = clamped_angle(
+        (upper_length * upper_length + reach * reach - lower_length * lower_length)
+            / (two * upper_length * reach),
+    );
+    let middle_angle_required = clamped_angle(
+        (upper_length * upper_length + lower_length * lower_length - reach * reach)
+            / (two * upper_length * lower_length),
+    );
+
+    let bend_axis = Unit::try_new((c - a).cross(&(b - a)), epsilon)
+        .or_else(|| Unit::try_new((c - a).cross(&(t - a)), epsilon))
+        .unwrap_or_else(|| {
+            // Chain is straight and in line with the target.
+            let direction = b - a;
+            let other = if direction.x.abs() < direction.y.abs() {
+                Vector3::x()
+            } else {
+                Vector3::y()
+            };
+            Unit::new_normalize(direction.cross(&other))
+        });
+
+    // Bend the chain to match the reach.
+    let upper_rotation = UnitQuaternion::from_axis_angle(
+        &(upper_global.rotation.inverse() * bend_axis),
+        upper_angle_required - upper_angle,
+    );
+    let middle_rotation = UnitQuaternion::from_axis_angle(
+        &(middle_global.rotation.inverse() * bend_axis),
+        middle_angle_required - middle_angle,
+    );
+    posture.append_rotation(upper, upper_rotation);
+    posture.append_rotation(middle, middle_rotation);
+
+    // Swing the chain toward the target.
+    let [upper_global, _, tip_global] = chain_globals(posture);
+    let c = tip_global.translation.vector;
+    let swing = UnitQuaternion::rotation_between(&(c - a), &(t - a))
+        .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&bend_axis, T::pi()));
+
+    let upper_global = upper_global.rotation;
+    posture.append_rotation(upper, upper_global.inverse() * swing * upper_global);
+}
+
+/// Returns `true` if all coordinates of the `point` are finite.
+pub(crate) fn is_finite_point<T>(point: &Point3<T>) -> bool
+where
+    T: RealField,
+{
+    point.coords.iter().all(|c| c.is_finite())
+}
+
+/// Returns `true` if all components of the `rotation` are finite.
+pub(crate) fn is_finite_rotation<T>(rotation: &UnitQuaternion<T>) -> bool
+where
+    T: RealField,
+{
+    rotation.coords.iter().all(|c| c.is_finite())
+}
+
+/// Marks `bones` with position goals in reusable `effectors` set
+/// for skelly with `len` bones.
+pub(crate) fn mark_effectors(
+    effectors: &mut Vec<bool>,
+    len: usize,
+    bones: impl IntoIterator<Item = usize>,
+) {
+    effectors.clear();
+    effectors.resize(len, false);
+    for bone in bones {
+        effectors[bone] = true;
+    }
+}
+
+/// Returns part of correction which sub-base in [`SolveMode::Chained`] mode
+/// passes to its ancestors when further goal can not be reached from it.
+pub(crate) fn sub_base_yield<T>() -> T
+where
+    T: RealField,
+{
+    na::convert(0.5)
+}
+
+/// Returns number of sub-bases above the `bone`,
+/// that is ancestors for which `is_effector` returns `true`.
+pub(crate) fn sub_base_depth<T, D>(
+    skelly: &Skelly<T, D>,
+    bone: usize,
+    is_effector: impl Fn(usize) -> bool,
+) -> usize
+where
+    T: Scalar,
+{
+    skelly
+        .iter_chain(bone)
+        .filter(|&ancestor| is_effector(ancestor))
+        .count()
+}
+
+/// Rotates effector `bone` so that its rest direction from the parent
+/// points from the parent toward the `goal`.
+pub(crate) fn align_effector<T, D>(
+    skelly: &Skelly<T, D>,
+    posture: &mut Posture<T>,
+    bone: usize,
+    goal: &Point3<T>,
+) where
+    T: RealField + Copy,
+{
+    if skelly.get_parent(bone).is_none() {
+        return;
+    }
+
+    let rest = skelly.get_isometry(bone);
+    let forward = rest
+        .rotation
+        .inverse_transform_vector(&rest.translation.vector);
+
+    let parent = posture.parent_global(bone, skelly);
+    let current = parent.rotation * posture.get_orientation(bone) * forward;
+    let required = goal - Point3::from(parent.translation.vector);
+
+    if let Some(rotation) = UnitQuaternion::rotation_between(&current, &required) {
+        posture.rotate_global(bone, skelly, rotation);
+    }
 }
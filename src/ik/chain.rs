@@ -0,0 +1,182 @@
+//! This module contains single-limb inverse-kinematic functionality for the skelly crate.
+
+use {
+    super::StepResult,
+    crate::skelly::{Posture, Skelly},
+    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion},
+};
+
+/// Chain of bones from a root to a tip captured from a skelly.
+///
+/// Solves single effector goals operating only on captured bone ids,
+/// without walking the skelly hierarchy on each step.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, ik::{chain::Chain, rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, Isometry3}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let shoulder = skelly.attach(Vector3::z(), root);
+/// let arm = skelly.attach(Vector3::x(), shoulder);
+/// let palm = skelly.attach(Vector3::x(), arm);
+///
+/// let target = Point3::new(1.0, 1.0, 1.0);
+///
+/// let mut chain = Chain::from_to(&skelly, shoulder, palm).unwrap();
+/// assert_eq!(chain.bones(), [shoulder, arm, palm]);
+///
+/// let mut chain_posture = Posture::new(&skelly);
+/// assert_eq!(chain.solve(&mut chain_posture, &target, 0.001, 100), StepResult::Solved);
+///
+/// let mut solver = RotorSolver::new(0.001);
+/// solver.set_position_goal(palm, target);
+/// let mut solver_posture = Posture::new(&skelly);
+/// for _ in 0..100 {
+///     solver.solve_step(&skelly, &mut solver_posture);
+/// }
+///
+/// let mut chain_globals = vec![Isometry3::identity(); skelly.len()];
+/// chain_posture.write_globals(&skelly, &Isometry3::identity(), &mut chain_globals);
+/// let mut solver_globals = vec![Isometry3::identity(); skelly.len()];
+/// solver_posture.write_globals(&skelly, &Isometry3::identity(), &mut solver_globals);
+///
+/// let chain_tip = chain_globals[palm].translation.vector;
+/// let solver_tip = solver_globals[palm].translation.vector;
+/// assert!((chain_tip - solver_tip).magnitude() < 0.01);
+/// assert!((chain_tip - target.coords).magnitude() < 0.01);
+/// ```
+pub struct Chain<T: Scalar> {
+    /// Bones from the topmost root to the tip.
+    path: Vec<usize>,
+    /// Index in `path` of the chain root.
+    first: usize,
+
+    // temp vector. saved to keep allocation.
+    globals: Vec<Isometry3<T>>,
+}
+
+impl<T> Clone for Chain<T>
+where
+    T: Scalar,
+{
+    fn clone(&self) -> Self {
+        Chain {
+            path: self.path.clone(),
+            first: self.first,
+            globals: Vec::new(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.path.clone_from(&source.path);
+        self.first = source.first;
+    }
+}
+
+impl<T> Chain<T>
+where
+    T: Scalar,
+{
+    /// Captures chain of bones from `root` to `tip`.
+    ///
+    /// Returns `None` if `root` is neither `tip` nor one of its ancestors.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `tip` index is out of bounds.
+    #[track_caller]
+    pub fn from_to<D>(skelly: &Skelly<T, D>, root: usize, tip: usize) -> Option<Self> {
+        let mut path = Vec::new();
+        path.push(tip);
+        path.extend(skelly.iter_chain(tip));
+        path.reverse();
+
+        let first = path.iter().position(|&bone| bone == root)?;
+
+        Some(Chain {
+            path,
+            first,
+            globals: Vec::new(),
+        })
+    }
+
+    /// Returns ids of bones in the chain from root to tip.
+    pub fn bones(&self) -> &[usize] {
+        &self.path[self.first..]
+    }
+
+    /// Returns id of the chain root bone.
+    pub fn root(&self) -> usize {
+        self.path[self.first]
+    }
+
+    /// Returns id of the chain tip bone.
+    pub fn tip(&self) -> usize {
+        self.path[self.path.len() - 1]
+    }
+
+    /// Performs one step moving chain tip toward `target`.
+    ///
+    /// Only bones of the chain are rotated.
+    /// The `posture` must be compatible with skelly used to capture the chain.
+    pub fn solve_step(
+        &mut self,
+        posture: &mut Posture<T>,
+        target: &Point3<T>,
+        epsilon: T,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        self.globals.clear();
+        let mut global = Isometry3::identity();
+        for &bone in &self.path {
+            global *= posture.get_isometry(bone);
+            self.globals.push(global);
+        }
+
+        let mut effector = Point3::from(global.translation.vector);
+        if effector.coords.metric_distance(&target.coords) < epsilon {
+            return StepResult::Solved;
+        }
+
+        for index in (self.first..self.path.len() - 1).rev() {
+            let global = &self.globals[index];
+            let inverse = global.inverse();
+
+            let effector_local = inverse * effector;
+            let target_local = inverse * target;
+
+            let required_rotation =
+                UnitQuaternion::rotation_between(&effector_local.coords, &target_local.coords)
+                    .unwrap_or_else(UnitQuaternion::identity);
+
+            posture.append_rotation(self.path[index], required_rotation);
+            effector = global * (required_rotation * effector_local);
+        }
+
+        StepResult::Unsolved
+    }
+
+    /// Performs up to `max_steps` steps moving chain tip toward `target`.
+    ///
+    /// Returns `StepResult::Solved` as soon as tip is within `epsilon` from `target`.
+    pub fn solve(
+        &mut self,
+        posture: &mut Posture<T>,
+        target: &Point3<T>,
+        epsilon: T,
+        max_steps: usize,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        for _ in 0..max_steps {
+            if let StepResult::Solved = self.solve_step(posture, target, epsilon) {
+                return StepResult::Solved;
+            }
+        }
+        self.solve_step(posture, target, epsilon)
+    }
+}
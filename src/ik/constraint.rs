@@ -0,0 +1,263 @@
+//! This module contains joint constraints for inverse-kinematic solvers.
+
+use {
+    crate::skelly::{Posture, Skelly},
+    na::{Quaternion, RealField, Scalar, Unit, UnitQuaternion, Vector3},
+};
+
+/// Limit imposed on joint rotation relative to the skelly rest orientation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JointConstraint<T: Scalar> {
+    /// Joint may rotate only about specified axis in its rest frame,
+    /// that is the frame of the joint in the skelly rest orientation.
+    Hinge(Unit<Vector3<T>>),
+
+    /// Joint may deviate from rest orientation by at most specified angle.
+    Cone(T),
+
+    /// Joint may not rotate.
+    Freeze,
+}
+
+impl<T> JointConstraint<T>
+where
+    T: RealField + Copy,
+{
+    /// Returns `rotation` limited by this constraint
+    /// with `rest` orientation of the joint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::ik::constraint::JointConstraint, na::{UnitQuaternion, Vector3}, core::f32::consts::FRAC_PI_2};
+    /// // Rest frame is rotated, its Z axis points along -Y of the parent.
+    /// let rest = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+    /// let hinge = JointConstraint::Hinge(Vector3::z_axis());
+    ///
+    /// // Rotation about hinge axis in the rest frame is kept.
+    /// let bend = rest * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.5);
+    /// assert!(hinge.constrain(&rest, &bend).angle_to(&bend) < 1e-5);
+    ///
+    /// // Same rotation in the parent's frame is a twist about another axis and is removed.
+    /// let twist = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.5) * rest;
+    /// assert!(hinge.constrain(&rest, &twist).angle_to(&rest) < 1e-5);
+    /// ```
+    pub fn constrain(
+        &self,
+        rest: &UnitQuaternion<T>,
+        rotation: &UnitQuaternion<T>,
+    ) -> UnitQuaternion<T> {
+        let deviation = rest.inverse() * rotation;
+
+        let deviation = match *self {
+            JointConstraint::Hinge(axis) => {
+                let vector = deviation.vector();
+                let projection = axis.into_inner() * vector.dot(&axis);
+                let twist = Quaternion::from_parts(deviation.scalar(), projection);
+                UnitQuaternion::try_new(twist, T::default_epsilon())
+                    .unwrap_or_else(UnitQuaternion::identity)
+            }
+            JointConstraint::Cone(max_angle) => {
+                if deviation.angle() > max_angle {
+                    match deviation.axis() {
+                        Some(axis) => UnitQuaternion::from_axis_angle(&axis, max_angle),
+                        None => deviation,
+                    }
+                } else {
+                    deviation
+                }
+            }
+            JointConstraint::Freeze => UnitQuaternion::identity(),
+        };
+
+        rest * deviation
+    }
+}
+
+/// Solver which enforces joint constraints.
+pub trait ConstrainedSolver<T: Scalar> {
+    /// Sets constraint for the `bone`, replacing previous one.
+    fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>);
+
+    /// Removes constraint for the `bone`.
+    fn remove_constraint(&mut self, bone: usize);
+}
+
+/// Set of joint constraints for a rig.
+///
+/// Constraints defined once may be applied to any solver.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, ik::{constraint::ConstraintSet, fabrik::FabrikSolver, rotor::RotorSolver}}, na::{Point3, Vector3, UnitQuaternion}, core::f32::consts::PI};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let shoulder = skelly.attach(Vector3::x(), root);
+/// let elbow = skelly.attach(Vector3::x(), shoulder);
+/// let palm = skelly.attach(Vector3::x(), elbow);
+///
+/// let mut constraints = ConstraintSet::new();
+/// constraints.set_freeze(root);
+/// constraints.set_cone(shoulder, PI / 8.0);
+/// constraints.set_hinge(elbow, Vector3::z_axis());
+///
+/// let mut fabrik = FabrikSolver::new(0.01);
+/// let mut rotor = RotorSolver::new(0.01);
+/// constraints.apply_to(&mut fabrik);
+/// constraints.apply_to(&mut rotor);
+///
+/// let target = Point3::new(0.0, 2.0, 1.0);
+/// fabrik.set_position_goal(palm, target);
+/// rotor.set_position_goal(palm, target);
+///
+/// let mut fabrik_posture = Posture::new(&skelly);
+/// let mut rotor_posture = Posture::new(&skelly);
+/// fabrik.solve(&skelly, &mut fabrik_posture, 20);
+/// rotor.solve(&skelly, &mut rotor_posture, 20);
+///
+/// for posture in [&mut fabrik_posture, &mut rotor_posture] {
+///     assert_eq!(*posture.get_orientation(root), UnitQuaternion::identity());
+///     assert!(posture.get_orientation(shoulder).angle() <= PI / 8.0 + 1e-5);
+///
+///     let elbow_axis = posture.get_orientation(elbow).axis();
+///     assert!(elbow_axis.map_or(true, |axis| axis.z.abs() > 0.9999));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConstraintSet<T: Scalar> {
+    constraints: Vec<(usize, JointConstraint<T>)>,
+}
+
+impl<T> Default for ConstraintSet<T>
+where
+    T: Scalar,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConstraintSet<T>
+where
+    T: Scalar,
+{
+    /// Returns new empty constraint set.
+    pub fn new() -> Self {
+        ConstraintSet {
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Sets constraint for the `bone`, replacing previous one.
+    pub fn set(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        set_constraint(&mut self.constraints, bone, constraint)
+    }
+
+    /// Restricts `bone` to rotate only about `axis` in its rest frame.
+    pub fn set_hinge(&mut self, bone: usize, axis: Unit<Vector3<T>>) {
+        self.set(bone, JointConstraint::Hinge(axis))
+    }
+
+    /// Restricts `bone` to deviate from rest orientation by at most `max_angle`.
+    pub fn set_cone(&mut self, bone: usize, max_angle: T) {
+        self.set(bone, JointConstraint::Cone(max_angle))
+    }
+
+    /// Forbids `bone` to rotate.
+    pub fn set_freeze(&mut self, bone: usize) {
+        self.set(bone, JointConstraint::Freeze)
+    }
+
+    /// Removes constraint for the `bone`.
+    pub fn remove(&mut self, bone: usize) {
+        self.constraints.retain(|(b, _)| *b != bone);
+    }
+
+    /// Returns constraint for the `bone`, if any.
+    pub fn get(&self, bone: usize) -> Option<&JointConstraint<T>> {
+        self.constraints
+            .iter()
+            .find(|(b, _)| *b == bone)
+            .map(|(_, constraint)| constraint)
+    }
+
+    /// Iterates over bones and their constraints.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &JointConstraint<T>)> + '_ {
+        self.constraints
+            .iter()
+            .map(|(bone, constraint)| (*bone, constraint))
+    }
+
+    /// Sets all constraints from this set to the `solver`.
+    pub fn apply_to(&self, solver: &mut impl ConstrainedSolver<T>) {
+        for (bone, constraint) in &self.constraints {
+            solver.set_constraint(*bone, constraint.clone());
+        }
+    }
+}
+
+pub(crate) fn set_constraint<T>(
+    constraints: &mut Vec<(usize, JointConstraint<T>)>,
+    bone: usize,
+    constraint: JointConstraint<T>,
+) where
+    T: Scalar,
+{
+    match constraints.iter_mut().find(|(b, _)| *b == bone) {
+        Some((_, c)) => *c = constraint,
+        None => constraints.push((bone, constraint)),
+    }
+}
+
+pub(crate) fn enforce_constraints<T, D>(
+    constraints: &[(usize, JointConstraint<T>)],
+    skelly: &Skelly<T, D>,
+    posture: &mut Posture<T>,
+) where
+    T: RealField + Copy,
+{
+    for (bone, constraint) in constraints {
+        if *bone < skelly.len() {
+            let rest = skelly.get_orientation(*bone);
+            let rotation = constraint.constrain(rest, posture.get_orientation(*bone));
+            posture.set_orientation(*bone, rotation);
+        }
+    }
+}
+
+pub(crate) fn enforce_curvature_limits<T, D>(
+    limits: &[(usize, T)],
+    skelly: &Skelly<T, D>,
+    posture: &mut Posture<T>,
+) where
+    T: RealField + Copy,
+{
+    for &(tip, max_angle) in limits {
+        if tip >= skelly.len() {
+            continue;
+        }
+
+        // Root orientation does not bend the chain.
+        let bends = || {
+            skelly
+                .iter_chain(tip)
+                .filter(move |&bone| skelly.get_parent(bone).is_some())
+        };
+
+        let mut total = T::zero();
+        for bone in bends() {
+            let rest = skelly.get_orientation(bone);
+            total += rest.angle_to(posture.get_orientation(bone));
+        }
+
+        if total > max_angle {
+            let factor = max_angle / total;
+            for bone in bends() {
+                let rest = *skelly.get_orientation(bone);
+                let deviation = rest.inverse() * posture.get_orientation(bone);
+                posture.set_orientation(bone, rest * deviation.powf(factor));
+            }
+        }
+    }
+}
@@ -1,9 +1,18 @@
 //! This module contains inverse-kinematic functionality for the skelly crate.
 
 use {
-    super::{IkSolver, StepResult},
+    super::{
+        align_effector,
+        constraint::{
+            enforce_constraints, enforce_curvature_limits, set_constraint, ConstrainedSolver,
+            JointConstraint,
+        },
+        ease_toward, is_finite_point, is_finite_rotation, mark_effectors, solve_steps,
+        step_in_place, sub_base_depth, sub_base_yield, ErrorNorm, EulerOrder, GoalSolver, IkSolver,
+        InPlace, RootMode, SolveMode, StepResult, DEFAULT_UPDATE_STEPS,
+    },
     crate::skelly::{Posture, Skelly},
-    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion},
+    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion, Vector3},
 };
 
 #[derive(Clone, Copy)]
@@ -11,16 +20,47 @@ struct IkGoal<T: Scalar> {
     bone: usize,
     position: Option<Point3<T>>,
     orientation: Option<UnitQuaternion<T>>,
+
+    // requested position and speed for smoothed goals.
+    target: Option<Point3<T>>,
+    speed: Option<T>,
+
+    // cached chain root and length.
+    reach: Option<(usize, T)>,
+
+    // correction falloff per level from the effector.
+    falloff: Option<T>,
 }
 pub struct FabrikSolver<T: Scalar> {
     epsilon: T,
     min_len: usize,
     goals: Vec<IkGoal<T>>,
+    solve_mode: SolveMode,
+    error_norm: ErrorNorm,
+    root_mode: RootMode,
+    next_goal: usize,
+    last_error: Option<T>,
+    error_delta: Option<T>,
+    update_steps: usize,
+    min_improvement: Option<T>,
+    orientation_weight: Option<T>,
+    constraints: Vec<(usize, JointConstraint<T>)>,
+    curvature_limits: Vec<(usize, T)>,
+    rigid_subtrees: Vec<usize>,
+    aligned_effectors: Vec<usize>,
+
+    // revision of the skelly cached lengths were computed for.
+    cache_revision: Option<u64>,
+
+    // cached rest lengths of bones.
+    lengths: Vec<T>,
 
     // temp vectors. saved to keep allocation.
     forward_queue: Vec<QueueItem<T>>,
-    backward_queue: Vec<QueueItem<T>>,
+    points: Vec<Option<Point3<T>>>,
     globals: Vec<Isometry3<T>>,
+    effectors: Vec<bool>,
+    in_place: Option<InPlace<T>>,
 }
 
 impl<T> Clone for FabrikSolver<T>
@@ -32,9 +72,26 @@ where
             epsilon: self.epsilon.clone(),
             min_len: self.min_len,
             goals: self.goals.clone(),
+            solve_mode: self.solve_mode,
+            error_norm: self.error_norm,
+            root_mode: self.root_mode,
+            next_goal: self.next_goal,
+            last_error: self.last_error.clone(),
+            error_delta: self.error_delta.clone(),
+            update_steps: self.update_steps,
+            min_improvement: self.min_improvement.clone(),
+            orientation_weight: self.orientation_weight.clone(),
+            constraints: self.constraints.clone(),
+            curvature_limits: self.curvature_limits.clone(),
+            rigid_subtrees: self.rigid_subtrees.clone(),
+            aligned_effectors: self.aligned_effectors.clone(),
+            cache_revision: self.cache_revision,
+            lengths: self.lengths.clone(),
             forward_queue: Vec::new(),
-            backward_queue: Vec::new(),
+            points: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
         }
     }
 
@@ -42,6 +99,21 @@ where
         self.epsilon = source.epsilon.clone();
         self.min_len = source.min_len;
         self.goals = source.goals.clone();
+        self.solve_mode = source.solve_mode;
+        self.error_norm = source.error_norm;
+        self.root_mode = source.root_mode;
+        self.next_goal = source.next_goal;
+        self.last_error = source.last_error.clone();
+        self.error_delta = source.error_delta.clone();
+        self.update_steps = source.update_steps;
+        self.min_improvement = source.min_improvement.clone();
+        self.orientation_weight = source.orientation_weight.clone();
+        self.constraints = source.constraints.clone();
+        self.curvature_limits = source.curvature_limits.clone();
+        self.rigid_subtrees = source.rigid_subtrees.clone();
+        self.aligned_effectors = source.aligned_effectors.clone();
+        self.cache_revision = source.cache_revision;
+        self.lengths = source.lengths.clone();
     }
 }
 
@@ -56,6 +128,36 @@ where
     fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult {
         self.solve_step(skelly, posture)
     }
+
+    fn last_error(&self) -> Option<T> {
+        self.last_error
+    }
+
+    fn min_improvement(&self) -> Option<T> {
+        self.min_improvement
+    }
+}
+
+impl<T> GoalSolver<T> for FabrikSolver<T>
+where
+    T: RealField + Copy,
+{
+    fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool {
+        self.set_position_goal(bone, position)
+    }
+}
+
+impl<T> ConstrainedSolver<T> for FabrikSolver<T>
+where
+    T: Scalar,
+{
+    fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        self.set_constraint(bone, constraint)
+    }
+
+    fn remove_constraint(&mut self, bone: usize) {
+        self.remove_constraint(bone)
+    }
 }
 
 impl<T> FabrikSolver<T>
@@ -65,10 +167,27 @@ where
     pub fn new(epsilon: T) -> Self {
         FabrikSolver {
             goals: Vec::new(),
+            solve_mode: SolveMode::Simultaneous,
+            error_norm: ErrorNorm::Sum,
+            root_mode: RootMode::Pinned,
+            next_goal: 0,
+            last_error: None,
+            error_delta: None,
+            update_steps: DEFAULT_UPDATE_STEPS,
+            min_improvement: None,
+            orientation_weight: None,
+            constraints: Vec::new(),
+            curvature_limits: Vec::new(),
+            rigid_subtrees: Vec::new(),
+            aligned_effectors: Vec::new(),
+            cache_revision: None,
+            lengths: Vec::new(),
             min_len: 0,
             forward_queue: Vec::new(),
-            backward_queue: Vec::new(),
+            points: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
             epsilon,
         }
     }
@@ -76,39 +195,125 @@ where
     pub fn new_one_way(epsilon: T) -> Self {
         FabrikSolver {
             goals: Vec::new(),
+            solve_mode: SolveMode::Simultaneous,
+            error_norm: ErrorNorm::Sum,
+            root_mode: RootMode::Pinned,
+            next_goal: 0,
+            last_error: None,
+            error_delta: None,
+            update_steps: DEFAULT_UPDATE_STEPS,
+            min_improvement: None,
+            orientation_weight: None,
+            constraints: Vec::new(),
+            curvature_limits: Vec::new(),
+            rigid_subtrees: Vec::new(),
+            aligned_effectors: Vec::new(),
+            cache_revision: None,
+            lengths: Vec::new(),
             min_len: 0,
             forward_queue: Vec::new(),
-            backward_queue: Vec::new(),
+            points: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
             epsilon,
         }
     }
 
-    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>)
+    /// Sets position goal for the `bone`.
+    ///
+    /// Position with non-finite coordinates is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
     {
+        if !is_finite_point(&position) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
-                    goal.position = Some(position);
+                    goal.target = Some(position);
+                    if goal.speed.is_none() || goal.position.is_none() {
+                        goal.position = Some(position);
+                    }
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: Some(position),
                     orientation: None,
+                    target: Some(position),
+                    speed: None,
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
+        true
     }
 
-    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>)
+    /// Sets position goal for the `bone` leading moving target.
+    ///
+    /// Effective goal is `position` advanced by `velocity` for `lead_time`.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_with_velocity(
+        &mut self,
+        bone: usize,
+        position: Point3<T>,
+        velocity: Vector3<T>,
+        lead_time: T,
+    ) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
+    {
+        self.set_position_goal(bone, position + velocity * lead_time)
+    }
+
+    /// Sets position goal for the `bone` in spherical coordinates around `base`.
+    ///
+    /// `azimuth` is angle in XY plane from X axis toward Y axis.
+    /// `elevation` is angle from XY plane toward Z axis.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_spherical(
+        &mut self,
+        bone: usize,
+        base: Point3<T>,
+        azimuth: T,
+        elevation: T,
+        distance: T,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        let direction = Vector3::new(
+            cos_elevation * cos_azimuth,
+            cos_elevation * sin_azimuth,
+            sin_elevation,
+        );
+        self.set_position_goal(bone, base + direction * distance)
+    }
+
+    /// Sets orientation goal for the `bone`.
+    ///
+    /// Orientation with non-finite components is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>) -> bool
+    where
+        T: RealField + Copy,
     {
+        if !is_finite_rotation(&orientation) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
@@ -116,16 +321,695 @@ where
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: None,
                     orientation: Some(orientation),
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: None,
+                })
+            }
+        }
+        true
+    }
+
+    /// Sets orientation goal for the `bone` from Euler `angles` in specified `order`.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_orientation_goal_euler_order(
+        &mut self,
+        bone: usize,
+        angles: [T; 3],
+        order: EulerOrder,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        self.set_orientation_goal(bone, order.rotation(angles))
+    }
+
+    /// Sets weight of orientation goals error.
+    ///
+    /// Orientation error is the angle in radians between global orientation
+    /// of the bone and its goal, multiplied by `weight`
+    /// to be commensurate with position error.
+    /// Default weight is one.
+    pub fn set_orientation_weight(&mut self, weight: T) {
+        self.orientation_weight = Some(weight);
+    }
+
+    /// Iterates over goals as bone id, position goal and orientation goal.
+    ///
+    /// Position goal is the final target, regardless of goal speed.
+    pub fn goals(
+        &self,
+    ) -> impl Iterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)> + '_
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .map(|goal| (goal.bone, goal.target, goal.orientation))
+    }
+
+    /// Sets goals from `iter` of bone id, position goal and orientation goal
+    /// as returned by `goals` method of any solver.
+    ///
+    /// Existing goals for other bones are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::ik::{fabrik::FabrikSolver, rotor::RotorSolver}, na::{Point3, UnitQuaternion}};
+    /// let mut fabrik = FabrikSolver::<f32>::new(0.01);
+    /// fabrik.set_position_goal(1, Point3::new(1.0, 2.0, 3.0));
+    /// fabrik.set_orientation_goal(1, UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3));
+    /// fabrik.set_orientation_goal(4, UnitQuaternion::from_euler_angles(0.4, 0.5, 0.6));
+    ///
+    /// let mut rotor = RotorSolver::new(0.01);
+    /// rotor.import_goals(fabrik.goals());
+    /// assert!(rotor.goals().eq(fabrik.goals()));
+    ///
+    /// let mut back = FabrikSolver::new(0.01);
+    /// back.import_goals(rotor.goals());
+    /// assert!(back.goals().eq(fabrik.goals()));
+    /// ```
+    pub fn import_goals(
+        &mut self,
+        iter: impl IntoIterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)>,
+    ) where
+        T: RealField + Copy,
+    {
+        for (bone, position, orientation) in iter {
+            if let Some(position) = position {
+                self.set_position_goal(bone, position);
+            }
+            if let Some(orientation) = orientation {
+                self.set_orientation_goal(bone, orientation);
+            }
+        }
+    }
+
+    /// Sets constraint for the `bone`, replacing previous one.
+    ///
+    /// Constraints are enforced at the end of each step.
+    pub fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        set_constraint(&mut self.constraints, bone, constraint)
+    }
+
+    /// Removes constraint for the `bone`.
+    pub fn remove_constraint(&mut self, bone: usize) {
+        self.constraints.retain(|(b, _)| *b != bone);
+    }
+
+    /// Sets whether subtree of the `bone` is carried rigidly when its parent rotates.
+    ///
+    /// By default, when a bone is rotated to move an effector,
+    /// its children are counter-rotated to keep their global orientation.\
+    /// With rigid subtree the `bone` keeps its orientation relative to the parent instead,
+    /// so the shape of the limb below a goal on an internal bone is preserved.
+    pub fn set_rigid_subtree(&mut self, bone: usize, rigid: bool) {
+        self.rigid_subtrees.retain(|b| *b != bone);
+        if rigid {
+            self.rigid_subtrees.push(bone);
+        }
+    }
+
+    /// Sets whether effector `bone` is aligned toward its position goal.
+    ///
+    /// After each step the effector is rotated so that its rest direction
+    /// from the parent points from the parent toward the position goal.\
+    /// This overrides orientation goal of the effector.
+    pub fn set_align_effector_to_goal(&mut self, bone: usize, align: bool) {
+        self.aligned_effectors.retain(|b| *b != bone);
+        if align {
+            self.aligned_effectors.push(bone);
+        }
+    }
+
+    /// Limits total bend of the chain from the `tip` up to its root.
+    ///
+    /// Bend is deviation of joints from rest orientation.
+    /// Orientation of the root bone is not counted.\
+    /// When total bend exceeds `max_angle` after a step,
+    /// bends of all joints are scaled down proportionally.
+    pub fn set_max_total_curvature(&mut self, tip: usize, max_angle: T) {
+        match self.curvature_limits.iter_mut().find(|(b, _)| *b == tip) {
+            Some((_, limit)) => *limit = max_angle,
+            None => self.curvature_limits.push((tip, max_angle)),
+        }
+    }
+
+    /// Sets falloff of corrections propagated from the effector `bone` toward the root.
+    ///
+    /// Rotation applied to the effector's parent is not scaled.
+    /// Each further level up the chain scales it by `factor` once more,
+    /// so joints far from the effector stay stable when `factor` is less than one.
+    pub fn set_propagation_falloff(&mut self, bone: usize, factor: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.falloff = Some(factor),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: Some(factor),
+                })
+            }
+        }
+    }
+
+    /// Sets speed with which position goal for the `bone` is eased toward requested position.
+    ///
+    /// Position goal moves by at most `speed` units per second
+    /// on each [`FabrikSolver::update`] call.
+    pub fn set_goal_speed(&mut self, bone: usize, speed: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.speed = Some(speed),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: Some(speed),
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
     }
 
+    /// Sets number of steps [`FabrikSolver::update`] performs.
+    ///
+    /// Defaults to [`DEFAULT_UPDATE_STEPS`].
+    pub fn set_update_steps(&mut self, steps: usize) {
+        self.update_steps = steps;
+    }
+
+    /// Sets minimal error change per step for [`FabrikSolver::solve`]
+    /// to keep going.
+    ///
+    /// Solving stops early when error changes by less than `min_improvement`
+    /// between steps, as further steps are unlikely to make progress.
+    /// The posture with the least error is then restored
+    /// and [`StepResult::Unsolved`] is returned.
+    pub fn set_min_improvement(&mut self, min_improvement: T) {
+        self.min_improvement = Some(min_improvement);
+    }
+
+    /// Reserves capacity for solving skelly with `skelly_len` bones
+    /// and up to `max_goals` goals.
+    ///
+    /// After reservation `solve_step` does not allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::fabrik::FabrikSolver}, na::{Point3, Vector3}};
+    /// use std::{alloc::{GlobalAlloc, Layout, System}, sync::atomic::{AtomicUsize, Ordering}};
+    ///
+    /// struct Counting;
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let left = skelly.attach(Vector3::x(), root);
+    /// let left_tip = skelly.attach(Vector3::x(), left);
+    /// let right = skelly.attach(-Vector3::x(), root);
+    /// let right_tip = skelly.attach(-Vector3::x(), right);
+    ///
+    /// let mut solver = FabrikSolver::new(0.001);
+    /// solver.reserve(skelly.len(), 2);
+    /// solver.set_position_goal(left_tip, Point3::new(1.0, 1.0, 0.0));
+    /// solver.set_position_goal(right_tip, Point3::new(-1.0, 0.0, 1.0));
+    /// let mut posture = Posture::new(&skelly);
+    ///
+    /// let before = ALLOCATIONS.load(Ordering::Relaxed);
+    /// for _ in 0..10 {
+    ///     solver.solve_step(&skelly, &mut posture);
+    /// }
+    /// assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    /// ```
+    pub fn reserve(&mut self, skelly_len: usize, max_goals: usize) {
+        let queue_len = skelly_len + max_goals;
+        self.goals
+            .reserve(max_goals.saturating_sub(self.goals.len()));
+        self.globals
+            .reserve(skelly_len.saturating_sub(self.globals.len()));
+        self.effectors
+            .reserve(skelly_len.saturating_sub(self.effectors.len()));
+        self.forward_queue
+            .reserve(queue_len.saturating_sub(self.forward_queue.len()));
+        self.points
+            .reserve(skelly_len.saturating_sub(self.points.len()));
+        self.lengths
+            .reserve(skelly_len.saturating_sub(self.lengths.len()));
+    }
+
+    /// Returns minimal number of bones skelly must have to be solved with this solver.
+    ///
+    /// This is the highest bone id with a goal plus one.
+    pub fn required_len(&self) -> usize {
+        self.min_len
+    }
+
+    /// Returns length of the chain from the `bone` to its root
+    /// cached when goal for the `bone` was last solved.
+    ///
+    /// Cached length is used to detect unreachable goals.
+    /// It is recomputed when the solved skelly is modified or another skelly is solved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{fabrik::FabrikSolver, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::new(0.0, 2.0, 0.0), bone);
+    ///
+    /// let mut solver = FabrikSolver::new(0.01);
+    /// solver.set_position_goal(tip, Point3::new(0.0, 0.0, 5.0));
+    /// assert_eq!(solver.cached_reach(tip), None);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve_step(&skelly, &mut posture), StepResult::Infeasible);
+    /// assert_eq!(solver.cached_reach(tip), Some(skelly.chain_length(tip)));
+    ///
+    /// // Modified skelly invalidates the cache.
+    /// skelly.set_position(tip, Vector3::new(0.0, 3.0, 0.0));
+    /// solver.solve_step(&skelly, &mut posture);
+    /// assert_eq!(solver.cached_reach(tip), Some(skelly.chain_length(tip)));
+    /// ```
+    pub fn cached_reach(&self, bone: usize) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .find(|goal| goal.bone == bone)
+            .and_then(|goal| goal.reach)
+            .map(|(_, reach)| reach)
+    }
+
+    /// Clears cached chain lengths.
+    ///
+    /// Cached lengths are cleared automatically when the solved skelly
+    /// is modified or another skelly is solved, so calling this is never required.
+    pub fn clear_reach_cache(&mut self) {
+        self.lengths.clear();
+        for goal in &mut self.goals {
+            goal.reach = None;
+        }
+    }
+
+    /// Captures rest lengths of the `skelly` bones.
+    ///
+    /// Forward and backward passes place each joint
+    /// at its cached length from the neighbor.\
+    /// Lengths are captured on the first step if this method is not called
+    /// and recaptured whenever the solved skelly is modified or another skelly is solved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::fabrik::FabrikSolver}, na::{Isometry3, Point3, Vector3}};
+    /// let build = |scale: f32| {
+    ///     let mut skelly = Skelly::<f32>::new();
+    ///     let root = skelly.add_root(Point3::origin());
+    ///     let elbow = skelly.attach(Vector3::new(1.0, 0.5, 0.0) * scale, root);
+    ///     let wrist = skelly.attach(Vector3::new(0.7, 0.0, 0.3) * scale, elbow);
+    ///     skelly.attach(Vector3::new(0.2, 0.1, 0.0) * scale, wrist);
+    ///     skelly
+    /// };
+    ///
+    /// let mut solver = FabrikSolver::new(0.0001);
+    /// solver.set_position_goal(3, Point3::new(0.3, 1.2, 0.8));
+    ///
+    /// // Same solver is reused for skelly of the same size with different lengths.
+    /// for skelly in [build(1.0), build(0.5)] {
+    ///     solver.prepare(&skelly);
+    ///     let mut posture = Posture::new(&skelly);
+    ///     solver.solve(&skelly, &mut posture, 100);
+    ///
+    ///     let mut globals = [Isometry3::identity(); 4];
+    ///     posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    ///     for bone in 1..4 {
+    ///         let parent = skelly.get_parent(bone).unwrap();
+    ///         let length = (globals[bone].translation.vector - globals[parent].translation.vector).magnitude();
+    ///         let rest = skelly.get_position(bone).magnitude();
+    ///         assert!((length - rest).abs() < 1e-5);
+    ///     }
+    /// }
+    /// ```
+    pub fn prepare<D>(&mut self, skelly: &Skelly<T, D>)
+    where
+        T: RealField + Copy,
+    {
+        self.lengths.clear();
+        self.lengths
+            .extend((0..skelly.len()).map(|bone| match skelly.get_parent(bone) {
+                Some(_) => skelly.get_isometry(bone).translation.vector.magnitude(),
+                None => T::zero(),
+            }));
+
+        for goal in &mut self.goals {
+            goal.reach = None;
+        }
+        self.cache_revision = Some(skelly.revision());
+    }
+
+    /// Returns total error of goals measured on the last step.
+    ///
+    /// Returns `None` if no steps were performed after goals change.
+    pub fn last_error(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.last_error
+    }
+
+    /// Returns `true` if the last step changed the error by less than `threshold`.
+    ///
+    /// A stalled solver is unlikely to make progress with further steps.
+    /// Returns `false` until at least two steps are performed after goals change.
+    pub fn is_stalled(&self, threshold: T) -> bool
+    where
+        T: RealField + Copy,
+    {
+        match self.error_delta {
+            Some(delta) => delta.abs() < threshold,
+            None => false,
+        }
+    }
+
+    fn reset_progress(&mut self) {
+        self.last_error = None;
+        self.error_delta = None;
+    }
+
+    /// Sets order in which multiple goals are processed.
+    ///
+    /// In [`SolveMode::Sequential`] mode each call to `solve_step`
+    /// advances one goal, cycling through all of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{fabrik::FabrikSolver, SolveMode}}, na::{Isometry3, Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let shoulder = skelly.attach(Vector3::x(), root);
+    /// let elbow = skelly.attach(Vector3::x(), shoulder);
+    /// let palm = skelly.attach(Vector3::x(), elbow);
+    ///
+    /// // Palm goal can not be reached from the elbow goal.
+    /// let elbow_goal = Point3::new(1.0, 1.0, 0.0);
+    /// let palm_goal = Point3::new(1.0, 3.5, 0.0);
+    ///
+    /// let mut solver = FabrikSolver::new(0.001);
+    /// solver.set_solve_mode(SolveMode::Chained);
+    /// solver.set_position_goal(elbow, elbow_goal);
+    /// solver.set_position_goal(palm, palm_goal);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// let mut globals = [Isometry3::identity(); 4];
+    /// let mut previous = globals;
+    /// for _ in 0..50 {
+    ///     previous = globals;
+    ///     solver.solve_step(&skelly, &mut posture);
+    ///     posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// }
+    ///
+    /// // Soft sub-base yields, so both goals are approached.
+    /// let elbow_error = (globals[elbow].translation.vector - elbow_goal.coords).magnitude();
+    /// let palm_error = (globals[palm].translation.vector - palm_goal.coords).magnitude();
+    /// assert!(elbow_error < 0.5);
+    /// assert!(palm_error < 1.5);
+    ///
+    /// // Without oscillation between steps.
+    /// let palm_motion = globals[palm].translation.vector - previous[palm].translation.vector;
+    /// assert!(palm_motion.magnitude() < 0.001);
+    /// ```
+    pub fn set_solve_mode(&mut self, mode: SolveMode) {
+        self.solve_mode = mode;
+        self.next_goal = 0;
+    }
+
+    /// Sets handling of root bones.
+    ///
+    /// With [`RootMode::Floating`] goals are never infeasible
+    /// as whole skelly may move toward them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{fabrik::FabrikSolver, RootMode, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let goal = Point3::new(0.0, 4.0, 0.0);
+    /// let solve = |mode| {
+    ///     let mut solver = FabrikSolver::new(0.001);
+    ///     solver.set_root_mode(mode);
+    ///     solver.set_position_goal(tip, goal);
+    ///
+    ///     let mut posture = Posture::new(&skelly);
+    ///     let result = solver.solve(&skelly, &mut posture, 100);
+    ///     (result, Point3::from(*posture.get_position(root)))
+    /// };
+    ///
+    /// let (result, position) = solve(RootMode::Pinned);
+    /// assert_eq!(result, StepResult::Infeasible);
+    /// assert_eq!(position, Point3::origin());
+    ///
+    /// let (result, position) = solve(RootMode::Floating);
+    /// assert_eq!(result, StepResult::Solved);
+    /// assert!((position - goal).magnitude() < (Point3::origin() - goal).magnitude());
+    /// ```
+    pub fn set_root_mode(&mut self, mode: RootMode) {
+        self.root_mode = mode;
+    }
+
+    /// Sets norm combining errors of multiple goals.
+    ///
+    /// Solver considers goals solved when combined error is less than its epsilon.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{fabrik::FabrikSolver, ErrorNorm, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let elbow = skelly.attach(Vector3::x(), root);
+    /// let palm = skelly.attach(Vector3::x(), elbow);
+    ///
+    /// let first_step = |norm| {
+    ///     let mut solver = FabrikSolver::new(0.01);
+    ///     solver.set_error_norm(norm);
+    ///     solver.set_position_goal(elbow, Point3::new(1.0, 0.0, 0.0));
+    ///     solver.set_position_goal(palm, Point3::new(2.0, 0.012, 0.0));
+    ///
+    ///     let mut posture = Posture::new(&skelly);
+    ///     solver.solve_step(&skelly, &mut posture)
+    /// };
+    ///
+    /// // Elbow goal is satisfied, palm goal is slightly off.
+    /// assert_eq!(first_step(ErrorNorm::RootMeanSquare), StepResult::Solved);
+    /// assert_eq!(first_step(ErrorNorm::Max), StepResult::Unsolved);
+    /// assert_eq!(first_step(ErrorNorm::Sum), StepResult::Unsolved);
+    /// ```
+    pub fn set_error_norm(&mut self, norm: ErrorNorm) {
+        self.error_norm = norm;
+    }
+
+    /// Performs up to `max_steps` steps toward solution.
+    ///
+    /// See [`IkSolver::solve`].
+    pub fn solve<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        IkSolver::solve(self, skelly, posture, max_steps)
+    }
+
+    /// Performs up to `max_steps` steps toward solution
+    /// calling `f` with step index and total error measured at the start of that step,
+    /// that is error of the posture produced by the previous step.
+    ///
+    /// Stops as soon as goals are solved or `f` returns `false`.\
+    /// Minimal improvement set with [`FabrikSolver::set_min_improvement`] is honored
+    /// as in [`FabrikSolver::solve`].\
+    /// Otherwise returns result of the last performed step.
+    pub fn solve_with_callback<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+        f: impl FnMut(usize, T) -> bool,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let min_improvement = self.min_improvement;
+        solve_steps(
+            skelly,
+            posture,
+            max_steps,
+            min_improvement,
+            |posture| {
+                let result = self.solve_step(skelly, posture);
+                (result, self.last_error)
+            },
+            f,
+        )
+    }
+
+    /// Advances smoothed goals by `dt` seconds and solves.
+    ///
+    /// Goals with speed set by [`FabrikSolver::set_goal_speed`]
+    /// are eased toward requested positions.
+    /// Then up to configured number of steps is performed.
+    pub fn update<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        dt: T,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        for goal in &mut self.goals {
+            if let (Some(speed), Some(target), Some(position)) =
+                (goal.speed, goal.target, goal.position)
+            {
+                let eased = ease_toward(position, target, speed, dt);
+                if eased != target {
+                    self.last_error = None;
+                    self.error_delta = None;
+                }
+                goal.position = Some(eased);
+            }
+        }
+
+        self.solve(skelly, posture, self.update_steps)
+    }
+
+    /// Performs one step toward solution
+    /// posing the `skelly` itself instead of separate posture.
+    ///
+    /// Solver keeps the pose the `skelly` had before the first step as its rest pose,
+    /// so constraints limit rotations relative to it and not to the pose written by previous steps.
+    /// Rest pose is recaptured when the `skelly` is changed between steps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, ik::{constraint::JointConstraint, fabrik::FabrikSolver}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// // Goal requires sharper bend than the constraint allows.
+    /// let mut solver = FabrikSolver::new(0.001);
+    /// solver.set_constraint(bone, JointConstraint::Cone(0.3));
+    /// solver.set_position_goal(tip, Point3::new(0.5, 0.5, 0.0));
+    ///
+    /// for _ in 0..50 {
+    ///     solver.solve_step_in_place(&mut skelly);
+    ///     assert!(skelly.get_orientation(bone).angle() <= 0.3 + 0.0001);
+    /// }
+    /// ```
+    pub fn solve_step_in_place<D>(&mut self, skelly: &mut Skelly<T, D>) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let mut in_place = self.in_place.take();
+        let result = step_in_place(&mut in_place, skelly, |skelly, posture| {
+            self.solve_step(skelly, posture)
+        });
+        self.in_place = in_place;
+        result
+    }
+
+    /// Performs one step toward solution.
+    ///
+    /// Each step moves joints from effectors to roots and back,
+    /// placing every joint at its bone length from the neighbor moved before it,
+    /// and then rotates joints to match placed points.\
+    /// Bone lengths are preserved regardless of number of steps.
+    ///
+    /// Solver without goals, including any solver for an empty skelly,
+    /// is always solved with zero error.
+    ///
+    /// Returns [`StepResult::Infeasible`] when a position goal is farther
+    /// from the pinned root of its chain than the chain length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::fabrik::FabrikSolver}, na::{Isometry3, Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let shoulder = skelly.attach(Vector3::new(1.0, 0.2, 0.0), root);
+    /// let elbow = skelly.attach(Vector3::new(0.8, 0.0, 0.3), shoulder);
+    /// let palm = skelly.attach(Vector3::new(0.5, 0.0, 0.0), elbow);
+    ///
+    /// let mut solver = FabrikSolver::new(0.0);
+    /// solver.set_position_goal(palm, Point3::new(-0.4, 1.1, 0.9));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// for _ in 0..100 {
+    ///     solver.solve_step(&skelly, &mut posture);
+    /// }
+    ///
+    /// let mut globals = [Isometry3::identity(); 4];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// for bone in [shoulder, elbow, palm] {
+    ///     let parent = skelly.get_parent(bone).unwrap();
+    ///     let length = (globals[bone].translation.vector - globals[parent].translation.vector).magnitude();
+    ///     let rest = skelly.get_isometry(bone).translation.vector.magnitude();
+    ///     assert!((length - rest).abs() < 1e-5);
+    /// }
+    ///
+    /// let palm_position = Point3::from(globals[palm].translation.vector);
+    /// assert!((palm_position - Point3::new(-0.4, 1.1, 0.9)).magnitude() < 1e-3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `posture` is not compatible with the `skelly`
+    /// or `skelly` has less bones than [`FabrikSolver::required_len`].
     pub fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult
     where
         T: RealField + Copy,
@@ -133,135 +1017,319 @@ where
         assert!(posture.is_compatible(skelly));
         assert!(self.min_len <= skelly.len());
 
+        if self.goals.is_empty() {
+            self.error_delta = self.last_error;
+            self.last_error = Some(T::zero());
+            return StepResult::Solved;
+        }
+
+        if self.cache_revision != Some(skelly.revision()) {
+            self.clear_reach_cache();
+            self.cache_revision = Some(skelly.revision());
+        }
+
+        if self.lengths.len() != skelly.len() {
+            self.prepare(skelly);
+        }
+
         self.globals.resize_with(skelly.len(), Isometry3::identity);
         posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
 
         self.forward_queue.clear();
-        self.backward_queue.clear();
+        self.points.clear();
+        self.points.resize(skelly.len(), None);
 
         let mut total_error = T::zero();
+        let mut terms = 0;
 
         // enque effectors
-        for goal in &self.goals {
-            if let Some(position) = goal.position {
+        let next_goal = self.next_goal % self.goals.len().max(1);
+        let mut infeasible = false;
+        for (index, goal) in self.goals.iter_mut().enumerate() {
+            if let Some(orientation) = goal.orientation.as_ref().filter(|q| is_finite_rotation(q)) {
+                let rotation = &self.globals[goal.bone].rotation;
+                let weight = self.orientation_weight.unwrap_or_else(T::one);
+                let error = rotation.angle_to(orientation) * weight;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+            }
+
+            if let Some(position) = goal.position.filter(is_finite_point) {
                 let effector = Point3::from(self.globals[goal.bone].translation.vector);
 
                 let error = position.coords.metric_distance(&effector.coords);
-                total_error += error;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+
+                let bone = goal.bone;
+                let lengths = &self.lengths;
+                let (root, reach) = *goal.reach.get_or_insert_with(|| {
+                    let root = skelly.iter_chain(bone).last().unwrap_or(bone);
+                    let reach = skelly
+                        .iter_chain(bone)
+                        .fold(lengths[bone], |reach, bone| reach + lengths[bone]);
+                    (root, reach)
+                });
+
+                let root_position = &self.globals[root].translation.vector;
+                if self.root_mode == RootMode::Pinned
+                    && position.coords.metric_distance(root_position) > reach + self.epsilon
+                {
+                    infeasible = true;
+                }
 
-                if let Some(parent) = skelly.get_parent(goal.bone) {
-                    enque(&mut self.forward_queue, parent, effector, position);
+                match self.solve_mode {
+                    SolveMode::Sequential if index != next_goal => continue,
+                    SolveMode::Chained => continue,
+                    _ => {}
+                }
+
+                if skelly.get_parent(goal.bone).is_some() {
+                    let falloff = goal.falloff.unwrap_or_else(T::one);
+                    enque(
+                        &mut self.forward_queue,
+                        goal.bone,
+                        position,
+                        T::one(),
+                        falloff,
+                    );
                 }
             }
         }
 
+        let total_error = self.error_norm.finish(total_error, terms);
+        self.error_delta = self.last_error.map(|last_error| last_error - total_error);
+        self.last_error = Some(total_error);
+
         if total_error < self.epsilon {
             return StepResult::Solved;
         }
 
-        // Traverse from effectors to roots.
-        while let Some((bone, effector, target)) = deque(&mut self.forward_queue) {
-            let global = &self.globals[bone];
-            let inverse = global.inverse();
+        if self.solve_mode == SolveMode::Sequential {
+            self.next_goal = next_goal + 1;
+        }
 
-            let old_effector_local = inverse * effector;
-            let target_local = inverse * target;
+        if self.solve_mode == SolveMode::Chained {
+            mark_effectors(
+                &mut self.effectors,
+                skelly.len(),
+                self.goals
+                    .iter()
+                    .filter(|goal| goal.position.filter(is_finite_point).is_some())
+                    .map(|goal| goal.bone),
+            );
+        }
 
-            let required_rotation =
-                UnitQuaternion::rotation_between(&old_effector_local.coords, &target_local.coords)
-                    .unwrap_or_else(UnitQuaternion::identity);
+        let mut depth = 0;
+        loop {
+            let mut deeper = false;
+            if self.solve_mode == SolveMode::Chained {
+                if depth > 0 {
+                    posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
+                    for point in &mut self.points {
+                        *point = None;
+                    }
+                }
 
-            posture.append_rotation(bone, required_rotation);
+                for goal in &self.goals {
+                    if let Some(position) = goal.position.filter(is_finite_point) {
+                        let goal_depth =
+                            sub_base_depth(skelly, goal.bone, |bone| self.effectors[bone]);
+                        deeper |= goal_depth > depth;
+                        if goal_depth != depth {
+                            continue;
+                        }
 
-            let required_rotation_child = required_rotation.inverse();
-            for child in skelly.iter_children(bone) {
-                let new_orientation = required_rotation_child * posture.get_orientation(child);
-                posture.set_orientation(child, new_orientation);
+                        if skelly.get_parent(goal.bone).is_some() {
+                            let falloff = goal.falloff.unwrap_or_else(T::one);
+                            enque(
+                                &mut self.forward_queue,
+                                goal.bone,
+                                position,
+                                T::one(),
+                                falloff,
+                            );
+                        }
+                    }
+                }
             }
 
-            let new_effector_local = required_rotation * old_effector_local;
-            let new_target_local = target_local - new_effector_local;
-
-            if let Some(parent) = skelly.get_parent(bone) {
-                enque(
-                    &mut self.forward_queue,
-                    parent,
-                    Point3::from(global.translation.vector),
-                    global * Point3::from(new_target_local),
-                );
-            } else {
-                enque(
-                    &mut self.backward_queue,
-                    usize::MAX - bone,
-                    global * Point3::from(new_target_local),
-                    Point3::from(global.translation.vector),
-                );
+            // Forward pass. Move joints from effectors to roots,
+            // placing each joint at its bone length from the moved child.
+            while let Some(QueueItem {
+                bone,
+                position,
+                weight,
+                falloff,
+            }) = deque(&mut self.forward_queue)
+            {
+                let current = Point3::from(self.globals[bone].translation.vector);
+                let moved = current + (position - current) * weight.min(T::one());
+                self.points[bone] = Some(moved);
+
+                if let Some(parent) = skelly.get_parent(bone) {
+                    let parent_position = Point3::from(self.globals[parent].translation.vector);
+
+                    // Sub-base stays in place while the bone can be placed at `moved` from it.
+                    // Otherwise it yields part of the correction.
+                    let mut weight = weight;
+                    if self.solve_mode == SolveMode::Chained && self.effectors[parent] {
+                        let distance = (moved - parent_position).magnitude();
+                        if (distance - self.lengths[bone]).abs() < self.epsilon {
+                            self.points[parent] = Some(parent_position);
+                            continue;
+                        }
+                        weight *= sub_base_yield();
+                    }
+
+                    let proposed = place(
+                        &moved,
+                        &parent_position,
+                        &(parent_position - current),
+                        self.lengths[bone],
+                        self.epsilon,
+                    );
+                    enque(
+                        &mut self.forward_queue,
+                        parent,
+                        proposed,
+                        weight * falloff,
+                        falloff,
+                    );
+                }
             }
-        }
 
-        // Traverse from roots to leafs.
-        while let Some((bone, effector, target)) = deque(&mut self.backward_queue) {
-            let bone = usize::MAX - bone;
+            // Backward pass. Place joints from roots to effectors.
+            // Parents precede children, so moved parent is always placed first.
+            for bone in 0..self.points.len() {
+                let moved = match self.points[bone] {
+                    Some(moved) => moved,
+                    None => continue,
+                };
 
-            let mut count = T::zero();
-            for _ in skelly.iter_children(bone) {
-                count += T::one();
+                let placed = match skelly.get_parent(bone) {
+                    Some(parent) => {
+                        let parent_placed = self.points[parent].unwrap_or_else(|| {
+                            Point3::from(self.globals[parent].translation.vector)
+                        });
+                        let current = &self.globals[bone].translation.vector;
+                        let parent_current = &self.globals[parent].translation.vector;
+                        place(
+                            &parent_placed,
+                            &moved,
+                            &(current - parent_current),
+                            self.lengths[bone],
+                            self.epsilon,
+                        )
+                    }
+                    None if self.root_mode == RootMode::Floating => moved,
+                    None => Point3::from(self.globals[bone].translation.vector),
+                };
+                self.points[bone] = Some(placed);
             }
 
-            let mut required_rotation = UnitQuaternion::identity();
-            for child in skelly.iter_children(bone) {
-                let global = self.globals[bone] * posture.get_isometry(child).translation;
-                let inverse = global.inverse();
+            // Rotate joints so that bones match placed points.
+            for bone in 0..self.points.len() {
+                let placed = match self.points[bone] {
+                    Some(placed) => placed,
+                    None => continue,
+                };
 
-                let old_effector_local = inverse * effector;
-                let target_local = inverse * target;
+                match skelly.get_parent(bone) {
+                    Some(parent) => {
+                        self.globals[bone] = self.globals[parent] * posture.get_isometry(bone);
+                    }
+                    None => {
+                        let delta = placed.coords - self.globals[bone].translation.vector;
+                        if delta.magnitude_squared() > T::zero() {
+                            posture.translate_global(bone, skelly, delta);
+                            self.globals[bone].translation.vector += delta;
+                        }
+                    }
+                }
 
-                let partial_rotation = UnitQuaternion::rotation_between(
-                    &old_effector_local.coords,
-                    &target_local.coords,
-                )
-                .map(|q| q.powf(T::one() / count))
-                .unwrap_or_else(UnitQuaternion::identity);
+                let global = self.globals[bone];
+                let origin = Point3::from(global.translation.vector);
 
-                required_rotation *= partial_rotation;
-            }
+                let mut count = T::zero();
+                for child in skelly.iter_children(bone) {
+                    if self.points[child].is_some() {
+                        count += T::one();
+                    }
+                }
 
-            posture.append_rotation(bone, required_rotation);
+                if count == T::zero() {
+                    continue;
+                }
 
-            let required_rotation_child = required_rotation.inverse();
-            for child in skelly.iter_children(bone) {
-                let new_orientation = required_rotation_child * posture.get_orientation(child);
-                posture.set_orientation(child, new_orientation);
+                let mut required_rotation = UnitQuaternion::identity();
+                for child in skelly.iter_children(bone) {
+                    if let Some(child_placed) = self.points[child] {
+                        let child_current = global * Point3::from(*posture.get_position(child));
+                        let partial_rotation = UnitQuaternion::rotation_between(
+                            &(child_current - origin),
+                            &(child_placed - origin),
+                        )
+                        .map(|q| q.powf(T::one() / count))
+                        .unwrap_or_else(UnitQuaternion::identity);
 
-                let global = self.globals[bone] * posture.get_isometry(child).translation;
-                let inverse = global.inverse();
+                        required_rotation = partial_rotation * required_rotation;
+                    }
+                }
+
+                // Renormalize to keep rounding errors from accumulating over steps.
+                let mut required_rotation_local =
+                    global.rotation.inverse() * required_rotation * global.rotation;
+                required_rotation_local.renormalize();
 
-                let old_effector_local = inverse * effector;
-                let target_local = inverse * target;
+                posture.append_rotation(bone, required_rotation_local);
+                self.globals[bone].rotation = global.rotation * required_rotation_local;
 
-                let new_effector_local = required_rotation * old_effector_local;
-                let new_target_local = target_local - new_effector_local;
+                let required_rotation_child = required_rotation_local.inverse();
+                for child in skelly.iter_children(bone) {
+                    if !self.rigid_subtrees.contains(&child) {
+                        let new_orientation =
+                            required_rotation_child * posture.get_orientation(child);
+                        posture.set_orientation(child, new_orientation);
+                    }
+                }
+            }
 
-                enque(
-                    &mut self.backward_queue,
-                    usize::MAX - child,
-                    global * posture.get_isometry(child).rotation * Point3::from(new_target_local),
-                    Point3::from(global.translation.vector),
-                );
+            if !deeper {
+                break;
             }
+            depth += 1;
         }
 
-        StepResult::Unsolved
+        for goal in &self.goals {
+            if let Some(position) = goal.position.as_ref().filter(|p| is_finite_point(p)) {
+                if self.aligned_effectors.contains(&goal.bone) {
+                    align_effector(skelly, posture, goal.bone, position);
+                }
+            }
+        }
+
+        enforce_constraints(&self.constraints, skelly, posture);
+        enforce_curvature_limits(&self.curvature_limits, skelly, posture);
+
+        if infeasible {
+            StepResult::Infeasible
+        } else {
+            StepResult::Unsolved
+        }
     }
 }
 
 struct QueueItem<T: Scalar> {
     bone: usize,
-    effector: Point3<T>,
-    target: Point3<T>,
+    position: Point3<T>,
+
+    // correction weight and its falloff per level toward root.
+    weight: T,
+    falloff: T,
 }
 
-fn enque<T>(queue: &mut Vec<QueueItem<T>>, bone: usize, effector: Point3<T>, target: Point3<T>)
+fn enque<T>(queue: &mut Vec<QueueItem<T>>, bone: usize, position: Point3<T>, weight: T, falloff: T)
 where
     T: Scalar,
 {
@@ -273,21 +1341,23 @@ where
         index,
         QueueItem {
             bone,
-            effector,
-            target,
+            position,
+            weight,
+            falloff,
         },
     );
 }
 
-fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<(usize, Point3<T>, Point3<T>)>
+fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<QueueItem<T>>
 where
     T: RealField + Copy,
 {
     let first = queue.pop()?;
     let mut count = T::one();
 
-    let mut effector_sum = first.effector.coords;
-    let mut target_sum = first.target.coords;
+    let mut position_sum = first.position.coords;
+    let mut weight_sum = first.weight;
+    let mut falloff_sum = first.falloff;
     while let Some(item) = queue.pop() {
         if item.bone != first.bone {
             queue.push(item);
@@ -295,12 +1365,35 @@ where
         }
 
         count += T::one();
-        effector_sum += item.effector.coords;
-        target_sum += item.target.coords;
+        position_sum += item.position.coords;
+        weight_sum += item.weight;
+        falloff_sum += item.falloff;
     }
 
-    let effector = Point3::from(effector_sum / count);
-    let target = Point3::from(target_sum / count);
+    Some(QueueItem {
+        bone: first.bone,
+        position: Point3::from(position_sum / count),
+        weight: weight_sum / count,
+        falloff: falloff_sum / count,
+    })
+}
+
+/// Returns point at `length` from `from` in direction toward `toward`.
+/// Uses `fallback` direction when points coincide.
+fn place<T>(
+    from: &Point3<T>,
+    toward: &Point3<T>,
+    fallback: &Vector3<T>,
+    length: T,
+    epsilon: T,
+) -> Point3<T>
+where
+    T: RealField + Copy,
+{
+    let direction = (toward - from)
+        .try_normalize(epsilon)
+        .or_else(|| fallback.try_normalize(epsilon))
+        .unwrap_or_else(Vector3::zeros);
 
-    Some((first.bone, effector, target))
+    from + direction * length
 }
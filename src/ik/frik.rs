@@ -1,9 +1,18 @@
 //! This module contains inverse-kinematic functionality for the skelly crate.
 
 use {
-    super::{IkSolver, StepResult},
+    super::{
+        align_effector,
+        constraint::{
+            enforce_constraints, enforce_curvature_limits, set_constraint, ConstrainedSolver,
+            JointConstraint,
+        },
+        ease_toward, is_finite_point, is_finite_rotation, mark_effectors, solve_steps,
+        step_in_place, sub_base_depth, sub_base_yield, ErrorNorm, EulerOrder, GoalSolver, IkSolver,
+        InPlace, RootMode, SolveMode, StepResult, DEFAULT_UPDATE_STEPS,
+    },
     crate::skelly::{Posture, Skelly},
-    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion},
+    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion, Vector3},
 };
 
 #[derive(Clone, Copy)]
@@ -11,15 +20,43 @@ struct IkGoal<T: Scalar> {
     bone: usize,
     position: Option<Point3<T>>,
     orientation: Option<UnitQuaternion<T>>,
+
+    // requested position and speed for smoothed goals.
+    target: Option<Point3<T>>,
+    speed: Option<T>,
+
+    // cached chain root and length.
+    reach: Option<(usize, T)>,
+
+    // correction falloff per level from the effector.
+    falloff: Option<T>,
 }
 pub struct FrikSolver<T: Scalar> {
     epsilon: T,
     min_len: usize,
     goals: Vec<IkGoal<T>>,
+    solve_mode: SolveMode,
+    error_norm: ErrorNorm,
+    root_mode: RootMode,
+    next_goal: usize,
+    last_error: Option<T>,
+    error_delta: Option<T>,
+    update_steps: usize,
+    min_improvement: Option<T>,
+    orientation_weight: Option<T>,
+    constraints: Vec<(usize, JointConstraint<T>)>,
+    curvature_limits: Vec<(usize, T)>,
+    rigid_subtrees: Vec<usize>,
+    aligned_effectors: Vec<usize>,
+
+    // revision of the skelly cached lengths were computed for.
+    cache_revision: Option<u64>,
 
     // temp vectors. saved to keep allocation.
     forward_queue: Vec<QueueItem<T>>,
     globals: Vec<Isometry3<T>>,
+    effectors: Vec<bool>,
+    in_place: Option<InPlace<T>>,
 }
 
 impl<T> Clone for FrikSolver<T>
@@ -31,8 +68,24 @@ where
             epsilon: self.epsilon.clone(),
             min_len: self.min_len,
             goals: self.goals.clone(),
+            solve_mode: self.solve_mode,
+            error_norm: self.error_norm,
+            root_mode: self.root_mode,
+            next_goal: self.next_goal,
+            last_error: self.last_error.clone(),
+            error_delta: self.error_delta.clone(),
+            update_steps: self.update_steps,
+            min_improvement: self.min_improvement.clone(),
+            orientation_weight: self.orientation_weight.clone(),
+            constraints: self.constraints.clone(),
+            curvature_limits: self.curvature_limits.clone(),
+            rigid_subtrees: self.rigid_subtrees.clone(),
+            aligned_effectors: self.aligned_effectors.clone(),
+            cache_revision: self.cache_revision,
             forward_queue: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
         }
     }
 
@@ -40,6 +93,20 @@ where
         self.epsilon = source.epsilon.clone();
         self.min_len = source.min_len;
         self.goals = source.goals.clone();
+        self.solve_mode = source.solve_mode;
+        self.error_norm = source.error_norm;
+        self.root_mode = source.root_mode;
+        self.next_goal = source.next_goal;
+        self.last_error = source.last_error.clone();
+        self.error_delta = source.error_delta.clone();
+        self.update_steps = source.update_steps;
+        self.min_improvement = source.min_improvement.clone();
+        self.orientation_weight = source.orientation_weight.clone();
+        self.constraints = source.constraints.clone();
+        self.curvature_limits = source.curvature_limits.clone();
+        self.rigid_subtrees = source.rigid_subtrees.clone();
+        self.aligned_effectors = source.aligned_effectors.clone();
+        self.cache_revision = source.cache_revision;
     }
 }
 
@@ -54,6 +121,36 @@ where
     fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult {
         self.solve_step(skelly, posture)
     }
+
+    fn last_error(&self) -> Option<T> {
+        self.last_error
+    }
+
+    fn min_improvement(&self) -> Option<T> {
+        self.min_improvement
+    }
+}
+
+impl<T> GoalSolver<T> for FrikSolver<T>
+where
+    T: RealField + Copy,
+{
+    fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool {
+        self.set_position_goal(bone, position)
+    }
+}
+
+impl<T> ConstrainedSolver<T> for FrikSolver<T>
+where
+    T: Scalar,
+{
+    fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        self.set_constraint(bone, constraint)
+    }
+
+    fn remove_constraint(&mut self, bone: usize) {
+        self.remove_constraint(bone)
+    }
 }
 
 impl<T> FrikSolver<T>
@@ -63,38 +160,142 @@ where
     pub fn new(epsilon: T) -> Self {
         FrikSolver {
             goals: Vec::new(),
+            solve_mode: SolveMode::Simultaneous,
+            error_norm: ErrorNorm::Sum,
+            root_mode: RootMode::Pinned,
+            next_goal: 0,
+            last_error: None,
+            error_delta: None,
+            update_steps: DEFAULT_UPDATE_STEPS,
+            min_improvement: None,
+            orientation_weight: None,
+            constraints: Vec::new(),
+            curvature_limits: Vec::new(),
+            rigid_subtrees: Vec::new(),
+            aligned_effectors: Vec::new(),
+            cache_revision: None,
             min_len: 0,
             forward_queue: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
             epsilon,
         }
     }
 
-    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>)
+    /// Sets position goal for the `bone`.
+    ///
+    /// Position with non-finite coordinates is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
     {
+        if !is_finite_point(&position) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
-                    goal.position = Some(position);
+                    goal.target = Some(position);
+                    if goal.speed.is_none() || goal.position.is_none() {
+                        goal.position = Some(position);
+                    }
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: Some(position),
                     orientation: None,
+                    target: Some(position),
+                    speed: None,
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
+        true
     }
 
-    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>)
+    /// Sets position goal for the `bone` leading moving target.
+    ///
+    /// Effective goal is `position` advanced by `velocity` for `lead_time`.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_with_velocity(
+        &mut self,
+        bone: usize,
+        position: Point3<T>,
+        velocity: Vector3<T>,
+        lead_time: T,
+    ) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
+    {
+        self.set_position_goal(bone, position + velocity * lead_time)
+    }
+
+    /// Sets position goal for the `bone` in spherical coordinates around `base`.
+    ///
+    /// `azimuth` is angle in XY plane from X axis toward Y axis.
+    /// `elevation` is angle from XY plane toward Z axis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::ik::frik::FrikSolver, na::{Point3, Vector3}};
+    /// let base = Point3::new(1.0, 2.0, 3.0);
+    /// let (azimuth, elevation, distance) = (0.3f32, 0.2f32, 2.0);
+    ///
+    /// let mut solver = FrikSolver::new(0.001);
+    /// solver.set_position_goal_spherical(1, base, azimuth, elevation, distance);
+    ///
+    /// let expected = base + Vector3::new(
+    ///     distance * elevation.cos() * azimuth.cos(),
+    ///     distance * elevation.cos() * azimuth.sin(),
+    ///     distance * elevation.sin(),
+    /// );
+    /// let (_, goal, _) = solver.goals().next().unwrap();
+    /// assert!((goal.unwrap() - expected).magnitude() < 1e-5);
+    /// ```
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_spherical(
+        &mut self,
+        bone: usize,
+        base: Point3<T>,
+        azimuth: T,
+        elevation: T,
+        distance: T,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        let direction = Vector3::new(
+            cos_elevation * cos_azimuth,
+            cos_elevation * sin_azimuth,
+            sin_elevation,
+        );
+        self.set_position_goal(bone, base + direction * distance)
+    }
+
+    /// Sets orientation goal for the `bone`.
+    ///
+    /// Orientation with non-finite components is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>) -> bool
+    where
+        T: RealField + Copy,
     {
+        if !is_finite_rotation(&orientation) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
@@ -102,16 +303,591 @@ where
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: None,
                     orientation: Some(orientation),
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: None,
+                })
+            }
+        }
+        true
+    }
+
+    /// Sets orientation goal for the `bone` from Euler `angles` in specified `order`.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_orientation_goal_euler_order(
+        &mut self,
+        bone: usize,
+        angles: [T; 3],
+        order: EulerOrder,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        self.set_orientation_goal(bone, order.rotation(angles))
+    }
+
+    /// Sets weight of orientation goals error.
+    ///
+    /// Orientation error is the angle in radians between global orientation
+    /// of the bone and its goal, multiplied by `weight`
+    /// to be commensurate with position error.
+    /// Default weight is one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::frik::FrikSolver}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let mut solver = FrikSolver::new(0.001);
+    /// solver.set_orientation_goal(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.3));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// solver.solve_step(&skelly, &mut posture);
+    /// assert!((solver.last_error().unwrap() - 0.3).abs() < 1e-5);
+    ///
+    /// solver.set_orientation_weight(2.0);
+    /// solver.solve_step(&skelly, &mut posture);
+    /// assert!((solver.last_error().unwrap() - 0.6).abs() < 1e-5);
+    /// ```
+    pub fn set_orientation_weight(&mut self, weight: T) {
+        self.orientation_weight = Some(weight);
+    }
+
+    /// Iterates over goals as bone id, position goal and orientation goal.
+    ///
+    /// Position goal is the final target, regardless of goal speed.
+    pub fn goals(
+        &self,
+    ) -> impl Iterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)> + '_
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .map(|goal| (goal.bone, goal.target, goal.orientation))
+    }
+
+    /// Sets goals from `iter` of bone id, position goal and orientation goal
+    /// as returned by `goals` method of any solver.
+    ///
+    /// Existing goals for other bones are kept.
+    pub fn import_goals(
+        &mut self,
+        iter: impl IntoIterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)>,
+    ) where
+        T: RealField + Copy,
+    {
+        for (bone, position, orientation) in iter {
+            if let Some(position) = position {
+                self.set_position_goal(bone, position);
+            }
+            if let Some(orientation) = orientation {
+                self.set_orientation_goal(bone, orientation);
+            }
+        }
+    }
+
+    /// Sets constraint for the `bone`, replacing previous one.
+    ///
+    /// Constraints are enforced at the end of each step.
+    pub fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        set_constraint(&mut self.constraints, bone, constraint)
+    }
+
+    /// Removes constraint for the `bone`.
+    pub fn remove_constraint(&mut self, bone: usize) {
+        self.constraints.retain(|(b, _)| *b != bone);
+    }
+
+    /// Sets whether subtree of the `bone` is carried rigidly when its parent rotates.
+    ///
+    /// By default, when a bone is rotated to move an effector,
+    /// its children are counter-rotated to keep their global orientation.\
+    /// With rigid subtree the `bone` keeps its orientation relative to the parent instead,
+    /// so the shape of the limb below a goal on an internal bone is preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{frik::FrikSolver, StepResult}}, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let elbow = skelly.attach(Vector3::x(), root);
+    /// let wrist = skelly.attach(Vector3::x(), elbow);
+    /// let palm = skelly.attach(Vector3::y(), wrist);
+    ///
+    /// let mut solver = FrikSolver::new(0.001);
+    /// solver.set_rigid_subtree(wrist, true);
+    /// solver.set_position_goal(wrist, Point3::new(1.0, 1.0, 0.0));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve(&skelly, &mut posture, 100), StepResult::Solved);
+    ///
+    /// // Wrist and palm keep their shape relative to the forearm.
+    /// assert!(posture.get_orientation(wrist).angle() < 1e-5);
+    ///
+    /// let mut globals = [Isometry3::identity(); 4];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let forearm = globals[wrist].translation.vector - globals[elbow].translation.vector;
+    /// let hand = globals[palm].translation.vector - globals[wrist].translation.vector;
+    /// assert!((forearm.angle(&hand) - core::f32::consts::FRAC_PI_2).abs() < 1e-4);
+    /// ```
+    pub fn set_rigid_subtree(&mut self, bone: usize, rigid: bool) {
+        self.rigid_subtrees.retain(|b| *b != bone);
+        if rigid {
+            self.rigid_subtrees.push(bone);
+        }
+    }
+
+    /// Sets whether effector `bone` is aligned toward its position goal.
+    ///
+    /// After each step the effector is rotated so that its rest direction
+    /// from the parent points from the parent toward the position goal.\
+    /// This overrides orientation goal of the effector.
+    pub fn set_align_effector_to_goal(&mut self, bone: usize, align: bool) {
+        self.aligned_effectors.retain(|b| *b != bone);
+        if align {
+            self.aligned_effectors.push(bone);
+        }
+    }
+
+    /// Limits total bend of the chain from the `tip` up to its root.
+    ///
+    /// Bend is deviation of joints from rest orientation.
+    /// Orientation of the root bone is not counted.\
+    /// When total bend exceeds `max_angle` after a step,
+    /// bends of all joints are scaled down proportionally.
+    pub fn set_max_total_curvature(&mut self, tip: usize, max_angle: T) {
+        match self.curvature_limits.iter_mut().find(|(b, _)| *b == tip) {
+            Some((_, limit)) => *limit = max_angle,
+            None => self.curvature_limits.push((tip, max_angle)),
+        }
+    }
+
+    /// Sets falloff of corrections propagated from the effector `bone` toward the root.
+    ///
+    /// Rotation applied to the effector's parent is not scaled.
+    /// Each further level up the chain scales it by `factor` once more,
+    /// so joints far from the effector stay stable when `factor` is less than one.
+    pub fn set_propagation_falloff(&mut self, bone: usize, factor: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.falloff = Some(factor),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: Some(factor),
+                })
+            }
+        }
+    }
+
+    /// Sets speed with which position goal for the `bone` is eased toward requested position.
+    ///
+    /// Position goal moves by at most `speed` units per second
+    /// on each [`FrikSolver::update`] call.
+    pub fn set_goal_speed(&mut self, bone: usize, speed: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.speed = Some(speed),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: Some(speed),
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
     }
 
+    /// Sets number of steps [`FrikSolver::update`] performs.
+    ///
+    /// Defaults to [`DEFAULT_UPDATE_STEPS`].
+    pub fn set_update_steps(&mut self, steps: usize) {
+        self.update_steps = steps;
+    }
+
+    /// Sets minimal error change per step for [`FrikSolver::solve`]
+    /// to keep going.
+    ///
+    /// Solving stops early when error changes by less than `min_improvement`
+    /// between steps, as further steps are unlikely to make progress.
+    /// The posture with the least error is then restored
+    /// and [`StepResult::Unsolved`] is returned.
+    pub fn set_min_improvement(&mut self, min_improvement: T) {
+        self.min_improvement = Some(min_improvement);
+    }
+
+    /// Reserves capacity for solving skelly with `skelly_len` bones
+    /// and up to `max_goals` goals.
+    ///
+    /// After reservation `solve_step` does not allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::frik::FrikSolver}, na::{Point3, Vector3}};
+    /// use std::{alloc::{GlobalAlloc, Layout, System}, sync::atomic::{AtomicUsize, Ordering}};
+    ///
+    /// struct Counting;
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let left = skelly.attach(Vector3::x(), root);
+    /// let left_tip = skelly.attach(Vector3::x(), left);
+    /// let right = skelly.attach(-Vector3::x(), root);
+    /// let right_tip = skelly.attach(-Vector3::x(), right);
+    ///
+    /// let mut solver = FrikSolver::new(0.001);
+    /// solver.reserve(skelly.len(), 2);
+    /// solver.set_position_goal(left_tip, Point3::new(1.0, 1.0, 0.0));
+    /// solver.set_position_goal(right_tip, Point3::new(-1.0, 0.0, 1.0));
+    /// let mut posture = Posture::new(&skelly);
+    ///
+    /// let before = ALLOCATIONS.load(Ordering::Relaxed);
+    /// for _ in 0..10 {
+    ///     solver.solve_step(&skelly, &mut posture);
+    /// }
+    /// assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    /// ```
+    pub fn reserve(&mut self, skelly_len: usize, max_goals: usize) {
+        let queue_len = skelly_len + max_goals;
+        self.goals
+            .reserve(max_goals.saturating_sub(self.goals.len()));
+        self.globals
+            .reserve(skelly_len.saturating_sub(self.globals.len()));
+        self.effectors
+            .reserve(skelly_len.saturating_sub(self.effectors.len()));
+        self.forward_queue
+            .reserve(queue_len.saturating_sub(self.forward_queue.len()));
+    }
+
+    /// Returns minimal number of bones skelly must have to be solved with this solver.
+    ///
+    /// This is the highest bone id with a goal plus one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, ik::frik::FrikSolver}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = FrikSolver::new(0.01);
+    /// assert_eq!(solver.required_len(), 0);
+    ///
+    /// solver.set_position_goal(tip, Point3::new(0.0, 2.0, 0.0));
+    /// solver.set_position_goal(bone, Point3::new(0.0, 1.0, 0.0));
+    /// assert_eq!(solver.required_len(), tip + 1);
+    /// assert!(solver.required_len() <= skelly.len());
+    /// ```
+    pub fn required_len(&self) -> usize {
+        self.min_len
+    }
+
+    /// Returns length of the chain from the `bone` to its root
+    /// cached when goal for the `bone` was last solved.
+    ///
+    /// Cached length is used to detect unreachable goals.
+    /// It is recomputed when the solved skelly is modified or another skelly is solved.
+    pub fn cached_reach(&self, bone: usize) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .find(|goal| goal.bone == bone)
+            .and_then(|goal| goal.reach)
+            .map(|(_, reach)| reach)
+    }
+
+    /// Clears cached chain lengths.
+    ///
+    /// Cached lengths are cleared automatically when the solved skelly
+    /// is modified or another skelly is solved, so calling this is never required.
+    pub fn clear_reach_cache(&mut self) {
+        for goal in &mut self.goals {
+            goal.reach = None;
+        }
+    }
+
+    /// Returns total error of goals measured on the last step.
+    ///
+    /// Returns `None` if no steps were performed after goals change.
+    pub fn last_error(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.last_error
+    }
+
+    /// Returns `true` if the last step changed the error by less than `threshold`.
+    ///
+    /// A stalled solver is unlikely to make progress with further steps.
+    /// Returns `false` until at least two steps are performed after goals change.
+    pub fn is_stalled(&self, threshold: T) -> bool
+    where
+        T: RealField + Copy,
+    {
+        match self.error_delta {
+            Some(delta) => delta.abs() < threshold,
+            None => false,
+        }
+    }
+
+    fn reset_progress(&mut self) {
+        self.last_error = None;
+        self.error_delta = None;
+    }
+
+    /// Sets order in which multiple goals are processed.
+    ///
+    /// In [`SolveMode::Sequential`] mode each call to `solve_step`
+    /// advances one goal, cycling through all of them.\
+    /// In [`SolveMode::Chained`] mode goals along the same chain
+    /// are solved from the root toward the tip.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{frik::FrikSolver, SolveMode, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let shoulder = skelly.attach(Vector3::x(), root);
+    /// let elbow = skelly.attach(Vector3::x(), shoulder);
+    /// let palm = skelly.attach(Vector3::x(), elbow);
+    ///
+    /// let mut solver = FrikSolver::new(0.001);
+    /// solver.set_solve_mode(SolveMode::Chained);
+    /// solver.set_position_goal(elbow, Point3::new(1.0, 1.0, 0.0));
+    /// solver.set_position_goal(palm, Point3::new(1.6, 1.0, 0.8));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// let mut errors = Vec::new();
+    /// let result = solver.solve_with_callback(&skelly, &mut posture, 20, |_, error| {
+    ///     errors.push(error);
+    ///     true
+    /// });
+    ///
+    /// assert_eq!(result, StepResult::Solved);
+    /// assert!(errors.windows(2).all(|pair| pair[1] <= pair[0]));
+    /// ```
+    pub fn set_solve_mode(&mut self, mode: SolveMode) {
+        self.solve_mode = mode;
+        self.next_goal = 0;
+    }
+
+    /// Sets handling of root bones.
+    ///
+    /// With [`RootMode::Floating`] goals are never infeasible
+    /// as whole skelly may move toward them.
+    pub fn set_root_mode(&mut self, mode: RootMode) {
+        self.root_mode = mode;
+    }
+
+    /// Sets norm combining errors of multiple goals.
+    ///
+    /// Solver considers goals solved when combined error is less than its epsilon.
+    pub fn set_error_norm(&mut self, norm: ErrorNorm) {
+        self.error_norm = norm;
+    }
+
+    /// Performs up to `max_steps` steps toward solution.
+    ///
+    /// See [`IkSolver::solve`].
+    pub fn solve<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        IkSolver::solve(self, skelly, posture, max_steps)
+    }
+
+    /// Performs up to `max_steps` steps toward solution
+    /// calling `f` with step index and total error measured at the start of that step,
+    /// that is error of the posture produced by the previous step.
+    ///
+    /// Stops as soon as goals are solved or `f` returns `false`.\
+    /// Minimal improvement set with [`FrikSolver::set_min_improvement`] is honored
+    /// as in [`FrikSolver::solve`].\
+    /// Otherwise returns result of the last performed step.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{frik::FrikSolver, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = FrikSolver::new(0.0001);
+    /// solver.set_position_goal(tip, Point3::new(0.5, 1.0, 0.5));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// let mut errors = Vec::new();
+    /// let result = solver.solve_with_callback(&skelly, &mut posture, 100, |step, error| {
+    ///     assert_eq!(step, errors.len());
+    ///     errors.push(error);
+    ///     step < 2
+    /// });
+    ///
+    /// assert_eq!(result, StepResult::Unsolved);
+    /// assert_eq!(errors.len(), 3);
+    /// assert!(errors.windows(2).all(|pair| pair[1] < pair[0]));
+    ///
+    /// // Out of reach goal stops solving once error stops changing.
+    /// solver.set_min_improvement(0.0001);
+    /// solver.set_position_goal(tip, Point3::new(0.0, 5.0, 0.0));
+    /// let mut steps = 0;
+    /// let result = solver.solve_with_callback(&skelly, &mut posture, 1000, |_, _| {
+    ///     steps += 1;
+    ///     true
+    /// });
+    ///
+    /// assert_eq!(result, StepResult::Unsolved);
+    /// assert!(steps < 1000);
+    /// ```
+    pub fn solve_with_callback<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+        f: impl FnMut(usize, T) -> bool,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let min_improvement = self.min_improvement;
+        solve_steps(
+            skelly,
+            posture,
+            max_steps,
+            min_improvement,
+            |posture| {
+                let result = self.solve_step(skelly, posture);
+                (result, self.last_error)
+            },
+            f,
+        )
+    }
+
+    /// Advances smoothed goals by `dt` seconds and solves.
+    ///
+    /// Goals with speed set by [`FrikSolver::set_goal_speed`]
+    /// are eased toward requested positions.
+    /// Then up to configured number of steps is performed.
+    pub fn update<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        dt: T,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        for goal in &mut self.goals {
+            if let (Some(speed), Some(target), Some(position)) =
+                (goal.speed, goal.target, goal.position)
+            {
+                let eased = ease_toward(position, target, speed, dt);
+                if eased != target {
+                    self.last_error = None;
+                    self.error_delta = None;
+                }
+                goal.position = Some(eased);
+            }
+        }
+
+        self.solve(skelly, posture, self.update_steps)
+    }
+
+    /// Performs one step toward solution
+    /// posing the `skelly` itself instead of separate posture.
+    ///
+    /// Solver keeps the pose the `skelly` had before the first step as its rest pose,
+    /// so constraints limit rotations relative to it and not to the pose written by previous steps.
+    /// Rest pose is recaptured when the `skelly` is changed between steps.
+    pub fn solve_step_in_place<D>(&mut self, skelly: &mut Skelly<T, D>) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let mut in_place = self.in_place.take();
+        let result = step_in_place(&mut in_place, skelly, |skelly, posture| {
+            self.solve_step(skelly, posture)
+        });
+        self.in_place = in_place;
+        result
+    }
+
+    /// Performs one step toward solution.
+    ///
+    /// Solver without goals, including any solver for an empty skelly,
+    /// is always solved with zero error.
+    ///
+    /// Returns [`StepResult::Infeasible`] when a position goal is farther
+    /// from the pinned root of its chain than the chain length.
+    ///
+    /// Joints that coincide with the effector or target are not rotated,
+    /// correction is propagated to their parents instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{frik::FrikSolver, StepResult}}, na::{Point3, Vector3}};
+    /// let empty = Skelly::<f32>::new();
+    /// let mut solver = FrikSolver::new(0.01);
+    /// assert_eq!(solver.solve_step(&empty, &mut Posture::new(&empty)), StepResult::Solved);
+    ///
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// assert_eq!(solver.solve_step(&skelly, &mut Posture::new(&skelly)), StepResult::Solved);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `posture` is not compatible with the `skelly`
+    /// or `skelly` has less bones than [`FrikSolver::required_len`].
     pub fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult
     where
         T: RealField + Copy,
@@ -119,65 +895,225 @@ where
         assert!(posture.is_compatible(skelly));
         assert!(self.min_len <= skelly.len());
 
+        if self.goals.is_empty() {
+            self.error_delta = self.last_error;
+            self.last_error = Some(T::zero());
+            return StepResult::Solved;
+        }
+
+        if self.cache_revision != Some(skelly.revision()) {
+            self.clear_reach_cache();
+            self.cache_revision = Some(skelly.revision());
+        }
+
         self.globals.resize_with(skelly.len(), Isometry3::identity);
         posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
 
         self.forward_queue.clear();
 
         let mut total_error = T::zero();
+        let mut terms = 0;
 
         // enque effectors
-        for goal in &self.goals {
-            if let Some(position) = goal.position {
+        let next_goal = self.next_goal % self.goals.len().max(1);
+        let mut infeasible = false;
+        for (index, goal) in self.goals.iter_mut().enumerate() {
+            if let Some(orientation) = goal.orientation.as_ref().filter(|q| is_finite_rotation(q)) {
+                let rotation = &self.globals[goal.bone].rotation;
+                let weight = self.orientation_weight.unwrap_or_else(T::one);
+                let error = rotation.angle_to(orientation) * weight;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+            }
+
+            if let Some(position) = goal.position.filter(is_finite_point) {
                 let effector = Point3::from(self.globals[goal.bone].translation.vector);
 
                 let error = position.coords.metric_distance(&effector.coords);
-                total_error += error;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+
+                let bone = goal.bone;
+                let (root, reach) = *goal.reach.get_or_insert_with(|| {
+                    let root = skelly.iter_chain(bone).last().unwrap_or(bone);
+                    (root, skelly.chain_length(bone))
+                });
+
+                let root_position = &self.globals[root].translation.vector;
+                if self.root_mode == RootMode::Pinned
+                    && position.coords.metric_distance(root_position) > reach + self.epsilon
+                {
+                    infeasible = true;
+                }
+
+                match self.solve_mode {
+                    SolveMode::Sequential if index != next_goal => continue,
+                    SolveMode::Chained => continue,
+                    _ => {}
+                }
 
                 if let Some(parent) = skelly.get_parent(goal.bone) {
-                    enque(&mut self.forward_queue, parent, effector, position);
+                    let falloff = goal.falloff.unwrap_or_else(T::one);
+                    enque(
+                        &mut self.forward_queue,
+                        parent,
+                        effector,
+                        position,
+                        T::one(),
+                        falloff,
+                    );
                 }
             }
         }
 
+        let total_error = self.error_norm.finish(total_error, terms);
+        self.error_delta = self.last_error.map(|last_error| last_error - total_error);
+        self.last_error = Some(total_error);
+
         if total_error < self.epsilon {
             return StepResult::Solved;
         }
 
-        // Traverse from effectors to roots.
-        while let Some((bone, effector, target)) = deque(&mut self.forward_queue) {
-            let global = &self.globals[bone];
-            let inverse = global.inverse();
+        if self.solve_mode == SolveMode::Sequential {
+            self.next_goal = next_goal + 1;
+        }
+
+        if self.solve_mode == SolveMode::Chained {
+            mark_effectors(
+                &mut self.effectors,
+                skelly.len(),
+                self.goals
+                    .iter()
+                    .filter(|goal| goal.position.filter(is_finite_point).is_some())
+                    .map(|goal| goal.bone),
+            );
+        }
+
+        let mut depth = 0;
+        loop {
+            let mut deeper = false;
+            if self.solve_mode == SolveMode::Chained {
+                if depth > 0 {
+                    posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
+                }
+
+                for goal in &self.goals {
+                    if let Some(position) = goal.position.filter(is_finite_point) {
+                        let goal_depth =
+                            sub_base_depth(skelly, goal.bone, |bone| self.effectors[bone]);
+                        deeper |= goal_depth > depth;
+                        if goal_depth != depth {
+                            continue;
+                        }
+
+                        if let Some(parent) = skelly.get_parent(goal.bone) {
+                            let effector = Point3::from(self.globals[goal.bone].translation.vector);
+                            let falloff = goal.falloff.unwrap_or_else(T::one);
+                            enque(
+                                &mut self.forward_queue,
+                                parent,
+                                effector,
+                                position,
+                                T::one(),
+                                falloff,
+                            );
+                        }
+                    }
+                }
+            }
+
+            // Traverse from effectors to roots.
+            while let Some(QueueItem {
+                bone,
+                effector,
+                target,
+                weight,
+                falloff,
+            }) = deque(&mut self.forward_queue)
+            {
+                let global = &self.globals[bone];
+                let inverse = global.inverse();
+
+                let old_effector_local = inverse * effector;
+                let target_local = inverse * target;
 
-            let old_effector_local = inverse * effector;
-            let target_local = inverse * target;
+                // Effector or target coincides with the joint.
+                // No meaningful rotation exists, leave it to the parent.
+                let degenerate = old_effector_local.coords.magnitude() < self.epsilon
+                    || target_local.coords.magnitude() < self.epsilon;
 
-            let required_rotation =
-                UnitQuaternion::rotation_between(&old_effector_local.coords, &target_local.coords)
-                    .unwrap_or_else(UnitQuaternion::identity);
+                let required_rotation = if degenerate {
+                    UnitQuaternion::identity()
+                } else {
+                    UnitQuaternion::rotation_between(
+                        &old_effector_local.coords,
+                        &target_local.coords,
+                    )
+                    .map(|q| if weight < T::one() { q.powf(weight) } else { q })
+                    .unwrap_or_else(UnitQuaternion::identity)
+                };
 
-            posture.append_rotation(bone, required_rotation);
+                posture.append_rotation(bone, required_rotation);
 
-            let required_rotation_child = required_rotation.inverse();
-            for child in skelly.iter_children(bone) {
-                let new_orientation = required_rotation_child * posture.get_orientation(child);
-                posture.set_orientation(child, new_orientation);
+                let required_rotation_child = required_rotation.inverse();
+                for child in skelly.iter_children(bone) {
+                    if !self.rigid_subtrees.contains(&child) {
+                        let new_orientation =
+                            required_rotation_child * posture.get_orientation(child);
+                        posture.set_orientation(child, new_orientation);
+                    }
+                }
+
+                let new_effector_local = required_rotation * old_effector_local;
+                let new_target_local = target_local - new_effector_local;
+
+                // Corrections do not propagate above sub-bases
+                // unless target can not be reached from them.
+                let mut weight = weight;
+                if self.solve_mode == SolveMode::Chained && self.effectors[bone] {
+                    if new_target_local.magnitude() < self.epsilon {
+                        continue;
+                    }
+                    weight *= sub_base_yield();
+                }
+
+                if let Some(parent) = skelly.get_parent(bone) {
+                    enque(
+                        &mut self.forward_queue,
+                        parent,
+                        Point3::from(global.translation.vector),
+                        global * Point3::from(new_target_local),
+                        weight * falloff,
+                        falloff,
+                    );
+                } else if self.root_mode == RootMode::Floating {
+                    let residual = global.rotation * new_target_local;
+                    posture.translate_global(bone, skelly, residual * weight);
+                }
             }
 
-            let new_effector_local = required_rotation * old_effector_local;
-            let new_target_local = target_local - new_effector_local;
+            if !deeper {
+                break;
+            }
+            depth += 1;
+        }
 
-            if let Some(parent) = skelly.get_parent(bone) {
-                enque(
-                    &mut self.forward_queue,
-                    parent,
-                    Point3::from(global.translation.vector),
-                    global * Point3::from(new_target_local),
-                );
+        for goal in &self.goals {
+            if let Some(position) = goal.position.as_ref().filter(|p| is_finite_point(p)) {
+                if self.aligned_effectors.contains(&goal.bone) {
+                    align_effector(skelly, posture, goal.bone, position);
+                }
             }
         }
 
-        StepResult::Unsolved
+        enforce_constraints(&self.constraints, skelly, posture);
+        enforce_curvature_limits(&self.curvature_limits, skelly, posture);
+
+        if infeasible {
+            StepResult::Infeasible
+        } else {
+            StepResult::Unsolved
+        }
     }
 }
 
@@ -185,10 +1121,20 @@ struct QueueItem<T: Scalar> {
     bone: usize,
     effector: Point3<T>,
     target: Point3<T>,
+
+    // correction weight and its falloff per level toward root.
+    weight: T,
+    falloff: T,
 }
 
-fn enque<T>(queue: &mut Vec<QueueItem<T>>, bone: usize, effector: Point3<T>, target: Point3<T>)
-where
+fn enque<T>(
+    queue: &mut Vec<QueueItem<T>>,
+    bone: usize,
+    effector: Point3<T>,
+    target: Point3<T>,
+    weight: T,
+    falloff: T,
+) where
     T: Scalar,
 {
     let index = queue
@@ -201,11 +1147,13 @@ where
             bone,
             effector,
             target,
+            weight,
+            falloff,
         },
     );
 }
 
-fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<(usize, Point3<T>, Point3<T>)>
+fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<QueueItem<T>>
 where
     T: RealField + Copy,
 {
@@ -214,6 +1162,8 @@ where
 
     let mut effector_sum = first.effector.coords;
     let mut target_sum = first.target.coords;
+    let mut weight_sum = first.weight;
+    let mut falloff_sum = first.falloff;
     while let Some(item) = queue.pop() {
         if item.bone != first.bone {
             queue.push(item);
@@ -223,10 +1173,15 @@ where
         count += T::one();
         effector_sum += item.effector.coords;
         target_sum += item.target.coords;
+        weight_sum += item.weight;
+        falloff_sum += item.falloff;
     }
 
-    let effector = Point3::from(effector_sum / count);
-    let target = Point3::from(target_sum / count);
-
-    Some((first.bone, effector, target))
+    Some(QueueItem {
+        bone: first.bone,
+        effector: Point3::from(effector_sum / count),
+        target: Point3::from(target_sum / count),
+        weight: weight_sum / count,
+        falloff: falloff_sum / count,
+    })
 }
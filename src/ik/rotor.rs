@@ -1,9 +1,18 @@
 //! This module contains inverse-kinematic functionality for the skelly crate.
 
 use {
-    super::{IkSolver, StepResult},
+    super::{
+        align_effector,
+        constraint::{
+            enforce_constraints, enforce_curvature_limits, set_constraint, ConstrainedSolver,
+            JointConstraint,
+        },
+        ease_toward, is_finite_point, is_finite_rotation, mark_effectors, solve_steps,
+        step_in_place, sub_base_depth, sub_base_yield, ErrorNorm, EulerOrder, GoalSolver, IkSolver,
+        InPlace, RootMode, SolveMode, StepResult, DEFAULT_UPDATE_STEPS,
+    },
     crate::skelly::{Posture, Skelly},
-    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion},
+    na::{Isometry3, Point3, RealField, Scalar, UnitQuaternion, Vector3},
 };
 
 #[derive(Clone, Copy)]
@@ -11,16 +20,43 @@ struct IkGoal<T: Scalar> {
     bone: usize,
     position: Option<Point3<T>>,
     orientation: Option<UnitQuaternion<T>>,
+
+    // requested position and speed for smoothed goals.
+    target: Option<Point3<T>>,
+    speed: Option<T>,
+
+    // cached chain root and length.
+    reach: Option<(usize, T)>,
+
+    // correction falloff per level from the effector.
+    falloff: Option<T>,
 }
 
 pub struct RotorSolver<T: Scalar> {
     epsilon: T,
     min_len: usize,
     goals: Vec<IkGoal<T>>,
+    solve_mode: SolveMode,
+    error_norm: ErrorNorm,
+    root_mode: RootMode,
+    next_goal: usize,
+    last_error: Option<T>,
+    error_delta: Option<T>,
+    update_steps: usize,
+    min_improvement: Option<T>,
+    orientation_weight: Option<T>,
+    constraints: Vec<(usize, JointConstraint<T>)>,
+    curvature_limits: Vec<(usize, T)>,
+    aligned_effectors: Vec<usize>,
+
+    // revision of the skelly cached lengths were computed for.
+    cache_revision: Option<u64>,
 
     // temp vectors. saved to keep allocation.
     queue: Vec<QueueItem<T>>,
     globals: Vec<Isometry3<T>>,
+    effectors: Vec<bool>,
+    in_place: Option<InPlace<T>>,
 }
 
 impl<T> Clone for RotorSolver<T>
@@ -32,8 +68,23 @@ where
             epsilon: self.epsilon.clone(),
             min_len: self.min_len,
             goals: self.goals.clone(),
+            solve_mode: self.solve_mode,
+            error_norm: self.error_norm,
+            root_mode: self.root_mode,
+            next_goal: self.next_goal,
+            last_error: self.last_error.clone(),
+            error_delta: self.error_delta.clone(),
+            update_steps: self.update_steps,
+            min_improvement: self.min_improvement.clone(),
+            orientation_weight: self.orientation_weight.clone(),
+            constraints: self.constraints.clone(),
+            curvature_limits: self.curvature_limits.clone(),
+            aligned_effectors: self.aligned_effectors.clone(),
+            cache_revision: self.cache_revision,
             queue: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
         }
     }
 
@@ -41,6 +92,19 @@ where
         self.epsilon = source.epsilon.clone();
         self.min_len = source.min_len;
         self.goals = source.goals.clone();
+        self.solve_mode = source.solve_mode;
+        self.error_norm = source.error_norm;
+        self.root_mode = source.root_mode;
+        self.next_goal = source.next_goal;
+        self.last_error = source.last_error.clone();
+        self.error_delta = source.error_delta.clone();
+        self.update_steps = source.update_steps;
+        self.min_improvement = source.min_improvement.clone();
+        self.orientation_weight = source.orientation_weight.clone();
+        self.constraints = source.constraints.clone();
+        self.curvature_limits = source.curvature_limits.clone();
+        self.aligned_effectors = source.aligned_effectors.clone();
+        self.cache_revision = source.cache_revision;
     }
 }
 
@@ -55,6 +119,36 @@ where
     fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult {
         self.solve_step(skelly, posture)
     }
+
+    fn last_error(&self) -> Option<T> {
+        self.last_error
+    }
+
+    fn min_improvement(&self) -> Option<T> {
+        self.min_improvement
+    }
+}
+
+impl<T> GoalSolver<T> for RotorSolver<T>
+where
+    T: RealField + Copy,
+{
+    fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool {
+        self.set_position_goal(bone, position)
+    }
+}
+
+impl<T> ConstrainedSolver<T> for RotorSolver<T>
+where
+    T: Scalar,
+{
+    fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        self.set_constraint(bone, constraint)
+    }
+
+    fn remove_constraint(&mut self, bone: usize) {
+        self.remove_constraint(bone)
+    }
 }
 
 impl<T> RotorSolver<T>
@@ -64,38 +158,167 @@ where
     pub fn new(epsilon: T) -> Self {
         RotorSolver {
             goals: Vec::new(),
+            solve_mode: SolveMode::Simultaneous,
+            error_norm: ErrorNorm::Sum,
+            root_mode: RootMode::Pinned,
+            next_goal: 0,
+            last_error: None,
+            error_delta: None,
+            update_steps: DEFAULT_UPDATE_STEPS,
+            min_improvement: None,
+            orientation_weight: None,
+            constraints: Vec::new(),
+            curvature_limits: Vec::new(),
+            aligned_effectors: Vec::new(),
+            cache_revision: None,
             min_len: 0,
             queue: Vec::new(),
             globals: Vec::new(),
+            effectors: Vec::new(),
+            in_place: None,
             epsilon,
         }
     }
 
-    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>)
+    /// Sets position goal for the `bone`.
+    ///
+    /// Position with non-finite coordinates is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = RotorSolver::new(0.01);
+    /// assert!(!solver.set_position_goal(tip, Point3::new(f32::NAN, 1.0, 0.0)));
+    /// assert!(!solver.set_orientation_goal(tip, UnitQuaternion::new_unchecked(na::Quaternion::new(f32::INFINITY, 0.0, 0.0, 0.0))));
+    /// assert_eq!(solver.goals().count(), 0);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve(&skelly, &mut posture, 10), StepResult::Solved);
+    /// for joint in [root, bone, tip] {
+    ///     assert_eq!(posture.get_isometry(joint), skelly.get_isometry(joint));
+    /// }
+    /// ```
+    pub fn set_position_goal(&mut self, bone: usize, position: Point3<T>) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
     {
+        if !is_finite_point(&position) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
-                    goal.position = Some(position);
+                    goal.target = Some(position);
+                    if goal.speed.is_none() || goal.position.is_none() {
+                        goal.position = Some(position);
+                    }
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: Some(position),
                     orientation: None,
+                    target: Some(position),
+                    speed: None,
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
+        true
     }
 
-    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>)
+    /// Sets position goal for the `bone` leading moving target.
+    ///
+    /// Effective goal is `position` advanced by `velocity` for `lead_time`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let position = Point3::new(1.0, 1.0, 0.0);
+    /// let velocity = Vector3::new(0.0, 0.0, 1.0);
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_position_goal_with_velocity(tip, position, velocity, 0.2);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve(&skelly, &mut posture, 100), StepResult::Solved);
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let lead = globals[tip].translation.vector - position.coords;
+    /// assert!((lead - velocity * 0.2).magnitude() < 0.001);
+    /// ```
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_with_velocity(
+        &mut self,
+        bone: usize,
+        position: Point3<T>,
+        velocity: Vector3<T>,
+        lead_time: T,
+    ) -> bool
     where
-        T: Copy,
+        T: RealField + Copy,
+    {
+        self.set_position_goal(bone, position + velocity * lead_time)
+    }
+
+    /// Sets position goal for the `bone` in spherical coordinates around `base`.
+    ///
+    /// `azimuth` is angle in XY plane from X axis toward Y axis.
+    /// `elevation` is angle from XY plane toward Z axis.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_position_goal_spherical(
+        &mut self,
+        bone: usize,
+        base: Point3<T>,
+        azimuth: T,
+        elevation: T,
+        distance: T,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        let direction = Vector3::new(
+            cos_elevation * cos_azimuth,
+            cos_elevation * sin_azimuth,
+            sin_elevation,
+        );
+        self.set_position_goal(bone, base + direction * distance)
+    }
+
+    /// Sets orientation goal for the `bone`.
+    ///
+    /// Orientation with non-finite components is rejected and `false` is returned,
+    /// so previous goal, if any, is kept.
+    pub fn set_orientation_goal(&mut self, bone: usize, orientation: UnitQuaternion<T>) -> bool
+    where
+        T: RealField + Copy,
     {
+        if !is_finite_rotation(&orientation) {
+            return false;
+        }
+        self.reset_progress();
         match self.goals.iter_mut().find(|goal| goal.bone == bone) {
             Some(goal) => {
                 if goal.bone == bone {
@@ -103,16 +326,666 @@ where
                 }
             }
             None => {
-                self.min_len = self.min_len.min(bone + 1);
+                self.min_len = self.min_len.max(bone + 1);
                 self.goals.push(IkGoal {
                     bone,
                     position: None,
                     orientation: Some(orientation),
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: None,
+                })
+            }
+        }
+        true
+    }
+
+    /// Sets orientation goal for the `bone` from Euler `angles` in specified `order`.
+    ///
+    /// Returns `false` if resulting goal is not finite and was rejected.
+    pub fn set_orientation_goal_euler_order(
+        &mut self,
+        bone: usize,
+        angles: [T; 3],
+        order: EulerOrder,
+    ) -> bool
+    where
+        T: RealField + Copy,
+    {
+        self.set_orientation_goal(bone, order.rotation(angles))
+    }
+
+    /// Sets weight of orientation goals error.
+    ///
+    /// Orientation error is the angle in radians between global orientation
+    /// of the bone and its goal, multiplied by `weight`
+    /// to be commensurate with position error.
+    /// Default weight is one.
+    pub fn set_orientation_weight(&mut self, weight: T) {
+        self.orientation_weight = Some(weight);
+    }
+
+    /// Iterates over goals as bone id, position goal and orientation goal.
+    ///
+    /// Position goal is the final target, regardless of goal speed.
+    pub fn goals(
+        &self,
+    ) -> impl Iterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)> + '_
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .map(|goal| (goal.bone, goal.target, goal.orientation))
+    }
+
+    /// Sets goals from `iter` of bone id, position goal and orientation goal
+    /// as returned by `goals` method of any solver.
+    ///
+    /// Existing goals for other bones are kept.
+    pub fn import_goals(
+        &mut self,
+        iter: impl IntoIterator<Item = (usize, Option<Point3<T>>, Option<UnitQuaternion<T>>)>,
+    ) where
+        T: RealField + Copy,
+    {
+        for (bone, position, orientation) in iter {
+            if let Some(position) = position {
+                self.set_position_goal(bone, position);
+            }
+            if let Some(orientation) = orientation {
+                self.set_orientation_goal(bone, orientation);
+            }
+        }
+    }
+
+    /// Sets constraint for the `bone`, replacing previous one.
+    ///
+    /// Constraints are enforced at the end of each step.
+    pub fn set_constraint(&mut self, bone: usize, constraint: JointConstraint<T>) {
+        set_constraint(&mut self.constraints, bone, constraint)
+    }
+
+    /// Removes constraint for the `bone`.
+    pub fn remove_constraint(&mut self, bone: usize) {
+        self.constraints.retain(|(b, _)| *b != bone);
+    }
+
+    /// Sets whether effector `bone` is aligned toward its position goal.
+    ///
+    /// After each step the effector is rotated so that its rest direction
+    /// from the parent points from the parent toward the position goal.\
+    /// This overrides orientation goal of the effector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let goal = Point3::new(1.0, 1.0, 0.0);
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_position_goal(tip, goal);
+    /// solver.set_align_effector_to_goal(tip, true);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// posture.set_orientation(tip, UnitQuaternion::from_euler_angles(0.5, 0.5, 0.5));
+    /// assert_eq!(solver.solve(&skelly, &mut posture, 100), StepResult::Solved);
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    ///
+    /// // Tip rest direction from its parent is X axis.
+    /// let forward = globals[tip].rotation * Vector3::x();
+    /// let expected = goal.coords - globals[bone].translation.vector;
+    /// assert!(forward.angle(&expected) < 1e-3);
+    /// ```
+    pub fn set_align_effector_to_goal(&mut self, bone: usize, align: bool) {
+        self.aligned_effectors.retain(|b| *b != bone);
+        if align {
+            self.aligned_effectors.push(bone);
+        }
+    }
+
+    /// Limits total bend of the chain from the `tip` up to its root.
+    ///
+    /// Bend is deviation of joints from rest orientation.
+    /// Orientation of the root bone is not counted.\
+    /// When total bend exceeds `max_angle` after a step,
+    /// bends of all joints are scaled down proportionally.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::rotor::RotorSolver}, na::{Point3, Vector3}, core::f32::consts::PI};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let mut tip = root;
+    /// for _ in 0..6 {
+    ///     tip = skelly.attach(Vector3::x(), tip);
+    /// }
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_max_total_curvature(tip, PI / 2.0);
+    /// solver.set_position_goal(tip, Point3::new(-1.0, 0.1, 0.0));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// solver.solve(&skelly, &mut posture, 50);
+    ///
+    /// let total: f32 = skelly
+    ///     .iter_chain(tip)
+    ///     .filter(|&bone| bone != root)
+    ///     .map(|bone| posture.get_orientation(bone).angle())
+    ///     .sum();
+    /// assert!(total <= PI / 2.0 + 1e-4);
+    /// ```
+    pub fn set_max_total_curvature(&mut self, tip: usize, max_angle: T) {
+        match self.curvature_limits.iter_mut().find(|(b, _)| *b == tip) {
+            Some((_, limit)) => *limit = max_angle,
+            None => self.curvature_limits.push((tip, max_angle)),
+        }
+    }
+
+    /// Sets falloff of corrections propagated from the effector `bone` toward the root.
+    ///
+    /// Rotation applied to the effector's parent is not scaled.
+    /// Each further level up the chain scales it by `factor` once more,
+    /// so joints far from the effector stay stable when `factor` is less than one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::rotor::RotorSolver}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let spine = skelly.attach(Vector3::z(), root);
+    /// let wrist = skelly.attach(Vector3::z(), spine);
+    /// let palm = skelly.attach(Vector3::z(), wrist);
+    ///
+    /// let solve = |falloff| {
+    ///     let mut solver = RotorSolver::new(0.001);
+    ///     solver.set_position_goal(palm, Point3::new(1.5, 0.0, 1.5));
+    ///     solver.set_propagation_falloff(palm, falloff);
+    ///     let mut posture = Posture::new(&skelly);
+    ///     solver.solve_step(&skelly, &mut posture);
+    ///     (posture.get_orientation(root).angle(), posture.get_orientation(wrist).angle())
+    /// };
+    ///
+    /// let (root_full, _) = solve(1.0);
+    /// let (root_damped, wrist_damped) = solve(0.5);
+    /// assert!(root_damped < wrist_damped);
+    /// assert!(root_damped < root_full);
+    /// ```
+    pub fn set_propagation_falloff(&mut self, bone: usize, factor: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.falloff = Some(factor),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: None,
+                    reach: None,
+                    falloff: Some(factor),
+                })
+            }
+        }
+    }
+
+    /// Sets speed with which position goal for the `bone` is eased toward requested position.
+    ///
+    /// Position goal moves by at most `speed` units per second
+    /// on each [`RotorSolver::update`] call.
+    pub fn set_goal_speed(&mut self, bone: usize, speed: T) {
+        match self.goals.iter_mut().find(|goal| goal.bone == bone) {
+            Some(goal) => goal.speed = Some(speed),
+            None => {
+                self.min_len = self.min_len.max(bone + 1);
+                self.goals.push(IkGoal {
+                    bone,
+                    position: None,
+                    orientation: None,
+                    target: None,
+                    speed: Some(speed),
+                    reach: None,
+                    falloff: None,
                 })
             }
         }
     }
 
+    /// Sets number of steps [`RotorSolver::update`] performs.
+    ///
+    /// Defaults to [`DEFAULT_UPDATE_STEPS`].
+    pub fn set_update_steps(&mut self, steps: usize) {
+        self.update_steps = steps;
+    }
+
+    /// Sets minimal error change per step for [`RotorSolver::solve`]
+    /// to keep going.
+    ///
+    /// Solving stops early when error changes by less than `min_improvement`
+    /// between steps, as further steps are unlikely to make progress.
+    /// The posture with the least error is then restored
+    /// and [`StepResult::Unsolved`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Isometry3, Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_min_improvement(0.0001);
+    /// solver.set_position_goal(tip, Point3::new(0.0, 5.0, 0.0));
+    ///
+    /// // Goal is out of reach. Solving stops once error stops changing
+    /// // instead of running all the steps.
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve(&skelly, &mut posture, usize::MAX), StepResult::Unsolved);
+    ///
+    /// // Chain is left stretched toward the goal.
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// assert!((globals[tip].translation.vector - Vector3::new(0.0, 2.0, 0.0)).norm() < 0.01);
+    /// ```
+    pub fn set_min_improvement(&mut self, min_improvement: T) {
+        self.min_improvement = Some(min_improvement);
+    }
+
+    /// Reserves capacity for solving skelly with `skelly_len` bones
+    /// and up to `max_goals` goals.
+    ///
+    /// After reservation `solve_step` does not allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::rotor::RotorSolver}, na::{Point3, Vector3}};
+    /// use std::{alloc::{GlobalAlloc, Layout, System}, sync::atomic::{AtomicUsize, Ordering}};
+    ///
+    /// struct Counting;
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let left = skelly.attach(Vector3::x(), root);
+    /// let left_tip = skelly.attach(Vector3::x(), left);
+    /// let right = skelly.attach(-Vector3::x(), root);
+    /// let right_tip = skelly.attach(-Vector3::x(), right);
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.reserve(skelly.len(), 2);
+    /// solver.set_position_goal(left_tip, Point3::new(1.0, 1.0, 0.0));
+    /// solver.set_position_goal(right_tip, Point3::new(-1.0, 0.0, 1.0));
+    /// let mut posture = Posture::new(&skelly);
+    ///
+    /// let before = ALLOCATIONS.load(Ordering::Relaxed);
+    /// for _ in 0..10 {
+    ///     solver.solve_step(&skelly, &mut posture);
+    /// }
+    /// assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    /// ```
+    pub fn reserve(&mut self, skelly_len: usize, max_goals: usize) {
+        let queue_len = skelly_len + max_goals;
+        self.goals
+            .reserve(max_goals.saturating_sub(self.goals.len()));
+        self.globals
+            .reserve(skelly_len.saturating_sub(self.globals.len()));
+        self.effectors
+            .reserve(skelly_len.saturating_sub(self.effectors.len()));
+        self.queue
+            .reserve(queue_len.saturating_sub(self.queue.len()));
+    }
+
+    /// Returns minimal number of bones skelly must have to be solved with this solver.
+    ///
+    /// This is the highest bone id with a goal plus one.
+    pub fn required_len(&self) -> usize {
+        self.min_len
+    }
+
+    /// Returns length of the chain from the `bone` to its root
+    /// cached when goal for the `bone` was last solved.
+    ///
+    /// Cached length is used to detect unreachable goals.
+    /// It is recomputed when the solved skelly is modified or another skelly is solved.
+    pub fn cached_reach(&self, bone: usize) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.goals
+            .iter()
+            .find(|goal| goal.bone == bone)
+            .and_then(|goal| goal.reach)
+            .map(|(_, reach)| reach)
+    }
+
+    /// Clears cached chain lengths.
+    ///
+    /// Cached lengths are cleared automatically when the solved skelly
+    /// is modified or another skelly is solved, so calling this is never required.
+    pub fn clear_reach_cache(&mut self) {
+        for goal in &mut self.goals {
+            goal.reach = None;
+        }
+    }
+
+    /// Returns total error of goals measured on the last step.
+    ///
+    /// Returns `None` if no steps were performed after goals change.
+    pub fn last_error(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.last_error
+    }
+
+    /// Returns `true` if the last step changed the error by less than `threshold`.
+    ///
+    /// A stalled solver is unlikely to make progress with further steps.
+    /// Returns `false` until at least two steps are performed after goals change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = RotorSolver::new(0.01);
+    /// solver.set_position_goal(tip, Point3::new(2.0, 1.0, 0.0));
+    /// let mut posture = Posture::new(&skelly);
+    /// solver.solve_step(&skelly, &mut posture);
+    /// solver.solve_step(&skelly, &mut posture);
+    /// assert!(!solver.is_stalled(0.001));
+    ///
+    /// // Goal is antiparallel to the chain. No rotation can be found.
+    /// let mut solver = RotorSolver::new(0.01);
+    /// solver.set_position_goal(tip, Point3::new(-2.0, 0.0, 0.0));
+    /// let mut posture = Posture::new(&skelly);
+    /// assert!(!solver.is_stalled(0.001));
+    /// for _ in 0..3 {
+    ///     assert_eq!(solver.solve_step(&skelly, &mut posture), StepResult::Unsolved);
+    /// }
+    /// assert!(solver.is_stalled(0.001));
+    /// ```
+    pub fn is_stalled(&self, threshold: T) -> bool
+    where
+        T: RealField + Copy,
+    {
+        match self.error_delta {
+            Some(delta) => delta.abs() < threshold,
+            None => false,
+        }
+    }
+
+    fn reset_progress(&mut self) {
+        self.last_error = None;
+        self.error_delta = None;
+    }
+
+    /// Sets order in which multiple goals are processed.
+    ///
+    /// In [`SolveMode::Sequential`] mode each call to `solve_step`
+    /// advances one goal, cycling through all of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, SolveMode, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let elbow = skelly.attach(Vector3::x(), root);
+    /// let wrist = skelly.attach(Vector3::x(), elbow);
+    /// let palm = skelly.attach(Vector3::x(), wrist);
+    ///
+    /// let solve = |mode| {
+    ///     let mut solver = RotorSolver::new(0.01);
+    ///     solver.set_solve_mode(mode);
+    ///     solver.set_position_goal(elbow, Point3::new(0.0, 1.0, 0.0));
+    ///     solver.set_position_goal(palm, Point3::new(0.0, 2.0, 1.0));
+    ///
+    ///     let mut posture = Posture::new(&skelly);
+    ///     (0..10).map(|_| solver.solve_step(&skelly, &mut posture)).last().unwrap()
+    /// };
+    ///
+    /// // Goals fight over shared joints when solved simultaneously.
+    /// assert_eq!(solve(SolveMode::Simultaneous), StepResult::Unsolved);
+    /// assert_eq!(solve(SolveMode::Sequential), StepResult::Solved);
+    /// ```
+    pub fn set_solve_mode(&mut self, mode: SolveMode) {
+        self.solve_mode = mode;
+        self.next_goal = 0;
+    }
+
+    /// Sets handling of root bones.
+    ///
+    /// With [`RootMode::Floating`] goals are never infeasible
+    /// as whole skelly may move toward them.
+    pub fn set_root_mode(&mut self, mode: RootMode) {
+        self.root_mode = mode;
+    }
+
+    /// Sets norm combining errors of multiple goals.
+    ///
+    /// Solver considers goals solved when combined error is less than its epsilon.
+    pub fn set_error_norm(&mut self, norm: ErrorNorm) {
+        self.error_norm = norm;
+    }
+
+    /// Performs up to `max_steps` steps toward solution.
+    ///
+    /// See [`IkSolver::solve`].
+    pub fn solve<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        IkSolver::solve(self, skelly, posture, max_steps)
+    }
+
+    /// Performs up to `max_steps` steps toward solution
+    /// calling `f` with step index and total error measured at the start of that step,
+    /// that is error of the posture produced by the previous step.
+    ///
+    /// Stops as soon as goals are solved or `f` returns `false`.\
+    /// Minimal improvement set with [`RotorSolver::set_min_improvement`] is honored
+    /// as in [`RotorSolver::solve`].\
+    /// Otherwise returns result of the last performed step.
+    pub fn solve_with_callback<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        max_steps: usize,
+        f: impl FnMut(usize, T) -> bool,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let min_improvement = self.min_improvement;
+        solve_steps(
+            skelly,
+            posture,
+            max_steps,
+            min_improvement,
+            |posture| {
+                let result = self.solve_step(skelly, posture);
+                (result, self.last_error)
+            },
+            f,
+        )
+    }
+
+    /// Advances smoothed goals by `dt` seconds and solves.
+    ///
+    /// Goals with speed set by [`RotorSolver::set_goal_speed`]
+    /// are eased toward requested positions.
+    /// Then up to configured number of steps is performed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_goal_speed(tip, 1.0);
+    /// solver.set_position_goal(tip, Point3::new(2.0, 0.0, 0.0));
+    /// solver.set_position_goal(tip, Point3::new(0.0, 1.5, 0.0));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// let mut last_distance = f32::MAX;
+    /// let mut last_effector = Vector3::new(2.0, 0.0, 0.0);
+    ///
+    /// let mut result = StepResult::Unsolved;
+    /// for _ in 0..100 {
+    ///     result = solver.update(&skelly, &mut posture, 0.1);
+    ///
+    ///     posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    ///     let effector = globals[tip].translation.vector;
+    ///
+    ///     // Effector moves smoothly toward the goal.
+    ///     let distance = (effector - Vector3::new(0.0, 1.5, 0.0)).magnitude();
+    ///     assert!(distance <= last_distance);
+    ///     assert!((effector - last_effector).magnitude() < 0.2);
+    ///     last_distance = distance;
+    ///     last_effector = effector;
+    /// }
+    ///
+    /// assert_eq!(result, StepResult::Solved);
+    /// assert!(last_distance < 0.001);
+    /// ```
+    pub fn update<D>(
+        &mut self,
+        skelly: &Skelly<T, D>,
+        posture: &mut Posture<T>,
+        dt: T,
+    ) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        for goal in &mut self.goals {
+            if let (Some(speed), Some(target), Some(position)) =
+                (goal.speed, goal.target, goal.position)
+            {
+                let eased = ease_toward(position, target, speed, dt);
+                if eased != target {
+                    self.last_error = None;
+                    self.error_delta = None;
+                }
+                goal.position = Some(eased);
+            }
+        }
+
+        self.solve(skelly, posture, self.update_steps)
+    }
+
+    /// Performs one step toward solution
+    /// posing the `skelly` itself instead of separate posture.
+    ///
+    /// Solver keeps the pose the `skelly` had before the first step as its rest pose,
+    /// so constraints limit rotations relative to it and not to the pose written by previous steps.
+    /// Rest pose is recaptured when the `skelly` is changed between steps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let target = Point3::new(1.0, 1.0, 0.0);
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_position_goal(tip, target);
+    ///
+    /// let mut result = StepResult::Unsolved;
+    /// for _ in 0..100 {
+    ///     result = solver.solve_step_in_place(&mut skelly);
+    /// }
+    /// assert_eq!(result, StepResult::Solved);
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    /// assert!((globals[tip].translation.vector - target.coords).magnitude() < 0.001);
+    /// ```
+    pub fn solve_step_in_place<D>(&mut self, skelly: &mut Skelly<T, D>) -> StepResult
+    where
+        T: RealField + Copy,
+    {
+        let mut in_place = self.in_place.take();
+        let result = step_in_place(&mut in_place, skelly, |skelly, posture| {
+            self.solve_step(skelly, posture)
+        });
+        self.in_place = in_place;
+        result
+    }
+
+    /// Performs one step toward solution.
+    ///
+    /// Solver without goals, including any solver for an empty skelly,
+    /// is always solved with zero error.
+    ///
+    /// Returns [`StepResult::Infeasible`] when a position goal is farther
+    /// from the pinned root of its chain than the chain length.
+    ///
+    /// Joints that coincide with the effector or target are not rotated,
+    /// correction is propagated to their parents instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture, ik::{rotor::RotorSolver, StepResult}}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::zeros(), bone);
+    ///
+    /// let mut solver = RotorSolver::new(0.001);
+    /// solver.set_position_goal(tip, Point3::new(0.0, 1.0, 0.0));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert_eq!(solver.solve(&skelly, &mut posture, 10), StepResult::Solved);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `posture` is not compatible with the `skelly`
+    /// or `skelly` has less bones than [`RotorSolver::required_len`].
     pub fn solve_step<D>(&mut self, skelly: &Skelly<T, D>, posture: &mut Posture<T>) -> StepResult
     where
         T: RealField + Copy,
@@ -120,61 +993,223 @@ where
         assert!(posture.is_compatible(skelly));
         assert!(self.min_len <= skelly.len());
 
+        if self.goals.is_empty() {
+            self.error_delta = self.last_error;
+            self.last_error = Some(T::zero());
+            return StepResult::Solved;
+        }
+
+        if self.cache_revision != Some(skelly.revision()) {
+            self.clear_reach_cache();
+            self.cache_revision = Some(skelly.revision());
+        }
+
         self.globals.resize_with(skelly.len(), Isometry3::identity);
         posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
 
+        self.queue.clear();
+
         let mut total_error = T::zero();
-        for goal in &self.goals {
-            if let Some(position) = goal.position {
+        let mut terms = 0;
+        let next_goal = self.next_goal % self.goals.len().max(1);
+        let mut infeasible = false;
+        for (index, goal) in self.goals.iter_mut().enumerate() {
+            if let Some(orientation) = goal.orientation.as_ref().filter(|q| is_finite_rotation(q)) {
+                let rotation = &self.globals[goal.bone].rotation;
+                let weight = self.orientation_weight.unwrap_or_else(T::one);
+                let error = rotation.angle_to(orientation) * weight;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+            }
+
+            if let Some(position) = goal.position.filter(is_finite_point) {
                 let effector = Point3::from(self.globals[goal.bone].translation.vector);
 
                 let error = position.coords.metric_distance(&effector.coords);
-                total_error += error;
+                total_error = self.error_norm.accumulate(total_error, error);
+                terms += 1;
+
+                let bone = goal.bone;
+                let (root, reach) = *goal.reach.get_or_insert_with(|| {
+                    let root = skelly.iter_chain(bone).last().unwrap_or(bone);
+                    (root, skelly.chain_length(bone))
+                });
+
+                let root_position = &self.globals[root].translation.vector;
+                if self.root_mode == RootMode::Pinned
+                    && position.coords.metric_distance(root_position) > reach + self.epsilon
+                {
+                    infeasible = true;
+                }
+
+                match self.solve_mode {
+                    SolveMode::Sequential if index != next_goal => continue,
+                    SolveMode::Chained => continue,
+                    _ => {}
+                }
 
                 if let Some(parent) = skelly.get_parent(goal.bone) {
-                    enque(&mut self.queue, parent, effector, position);
+                    let falloff = goal.falloff.unwrap_or_else(T::one);
+                    enque(
+                        &mut self.queue,
+                        parent,
+                        effector,
+                        position,
+                        T::one(),
+                        falloff,
+                    );
                 }
             }
         }
 
+        let total_error = self.error_norm.finish(total_error, terms);
+        self.error_delta = self.last_error.map(|last_error| last_error - total_error);
+        self.last_error = Some(total_error);
+
         if total_error < self.epsilon {
             return StepResult::Solved;
         }
 
-        while let Some((bone, effector, target)) = deque(&mut self.queue) {
-            let global = &self.globals[bone];
-            let inverse = global.inverse();
+        if self.solve_mode == SolveMode::Sequential {
+            self.next_goal = next_goal + 1;
+        }
+
+        if self.solve_mode == SolveMode::Chained {
+            mark_effectors(
+                &mut self.effectors,
+                skelly.len(),
+                self.goals
+                    .iter()
+                    .filter(|goal| goal.position.filter(is_finite_point).is_some())
+                    .map(|goal| goal.bone),
+            );
+        }
+
+        let mut depth = 0;
+        loop {
+            let mut deeper = false;
+            if self.solve_mode == SolveMode::Chained {
+                if depth > 0 {
+                    posture.write_globals(skelly, &Isometry3::identity(), &mut self.globals);
+                }
+
+                for goal in &self.goals {
+                    if let Some(position) = goal.position.filter(is_finite_point) {
+                        let goal_depth =
+                            sub_base_depth(skelly, goal.bone, |bone| self.effectors[bone]);
+                        deeper |= goal_depth > depth;
+                        if goal_depth != depth {
+                            continue;
+                        }
+
+                        if let Some(parent) = skelly.get_parent(goal.bone) {
+                            let effector = Point3::from(self.globals[goal.bone].translation.vector);
+                            let falloff = goal.falloff.unwrap_or_else(T::one);
+                            enque(
+                                &mut self.queue,
+                                parent,
+                                effector,
+                                position,
+                                T::one(),
+                                falloff,
+                            );
+                        }
+                    }
+                }
+            }
+
+            while let Some(QueueItem {
+                bone,
+                effector,
+                target,
+                weight,
+                falloff,
+            }) = deque(&mut self.queue)
+            {
+                // Corrections do not propagate above sub-bases
+                // unless target can not be reached from them.
+                let sub_base = self.solve_mode == SolveMode::Chained && self.effectors[bone];
+                let yielded = if sub_base {
+                    weight * sub_base_yield()
+                } else {
+                    weight
+                };
 
-            let mut effector_local = inverse * effector;
-            let target_local = inverse * target;
+                let global = &self.globals[bone];
+                let inverse = global.inverse();
 
-            // if effector_local.coords.magnitude_squared() < self.epsilon {
-            //     continue;
-            // }
+                let mut effector_local = inverse * effector;
+                let target_local = inverse * target;
 
-            // if target_local.coords.magnitude_squared() < self.epsilon {
-            //     continue;
-            // }
+                // Effector or target coincides with the joint.
+                // No meaningful rotation exists, leave it to the parent.
+                if effector_local.coords.magnitude() < self.epsilon
+                    || target_local.coords.magnitude() < self.epsilon
+                {
+                    if let Some(parent) = skelly.get_parent(bone) {
+                        enque(
+                            &mut self.queue,
+                            parent,
+                            effector,
+                            target,
+                            yielded * falloff,
+                            falloff,
+                        );
+                    }
+                    continue;
+                }
+
+                let required_rotation =
+                    UnitQuaternion::rotation_between(&effector_local.coords, &target_local.coords)
+                        .map(|q| if weight < T::one() { q.powf(weight) } else { q })
+                        .unwrap_or_else(UnitQuaternion::identity);
+
+                posture.append_rotation(bone, required_rotation);
+                effector_local = required_rotation * effector_local;
 
-            let required_rotation =
-                UnitQuaternion::rotation_between(&effector_local.coords, &target_local.coords)
-                    .unwrap_or_else(UnitQuaternion::identity);
+                let error = effector_local.coords.metric_distance(&target_local.coords);
+                if error < self.epsilon {
+                    continue;
+                }
 
-            posture.append_rotation(bone, required_rotation);
-            effector_local = required_rotation * effector_local;
+                if let Some(parent) = skelly.get_parent(bone) {
+                    let effector = global * effector_local;
+                    enque(
+                        &mut self.queue,
+                        parent,
+                        effector,
+                        target,
+                        yielded * falloff,
+                        falloff,
+                    );
+                } else if self.root_mode == RootMode::Floating {
+                    let residual = global.rotation * (target_local - effector_local);
+                    posture.translate_global(bone, skelly, residual * yielded);
+                }
+            }
 
-            let error = effector_local.coords.metric_distance(&target_local.coords);
-            if error < self.epsilon {
-                continue;
+            if !deeper {
+                break;
             }
+            depth += 1;
+        }
 
-            if let Some(parent) = skelly.get_parent(bone) {
-                let effector = global * effector_local;
-                enque(&mut self.queue, parent, effector, target);
+        for goal in &self.goals {
+            if let Some(position) = goal.position.as_ref().filter(|p| is_finite_point(p)) {
+                if self.aligned_effectors.contains(&goal.bone) {
+                    align_effector(skelly, posture, goal.bone, position);
+                }
             }
         }
 
-        StepResult::Unsolved
+        enforce_constraints(&self.constraints, skelly, posture);
+        enforce_curvature_limits(&self.curvature_limits, skelly, posture);
+
+        if infeasible {
+            StepResult::Infeasible
+        } else {
+            StepResult::Unsolved
+        }
     }
 }
 
@@ -183,10 +1218,20 @@ struct QueueItem<T: Scalar> {
     bone: usize,
     effector: Point3<T>,
     target: Point3<T>,
+
+    // correction weight and its falloff per level toward root.
+    weight: T,
+    falloff: T,
 }
 
-fn enque<T>(queue: &mut Vec<QueueItem<T>>, bone: usize, effector: Point3<T>, target: Point3<T>)
-where
+fn enque<T>(
+    queue: &mut Vec<QueueItem<T>>,
+    bone: usize,
+    effector: Point3<T>,
+    target: Point3<T>,
+    weight: T,
+    falloff: T,
+) where
     T: Scalar,
 {
     let index = queue
@@ -199,19 +1244,23 @@ where
             bone,
             effector,
             target,
+            weight,
+            falloff,
         },
     );
 }
 
-fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<(usize, Point3<T>, Point3<T>)>
+fn deque<T>(queue: &mut Vec<QueueItem<T>>) -> Option<QueueItem<T>>
 where
     T: RealField + Copy,
 {
     let first = queue.pop()?;
-
     let mut count = T::one();
+
     let mut effector_sum = first.effector.coords;
     let mut target_sum = first.target.coords;
+    let mut weight_sum = first.weight;
+    let mut falloff_sum = first.falloff;
     while let Some(item) = queue.pop() {
         if item.bone != first.bone {
             queue.push(item);
@@ -221,11 +1270,15 @@ where
         count += T::one();
         effector_sum += item.effector.coords;
         target_sum += item.target.coords;
+        weight_sum += item.weight;
+        falloff_sum += item.falloff;
     }
 
-    Some((
-        first.bone,
-        Point3::from(effector_sum / count),
-        Point3::from(target_sum / count),
-    ))
+    Some(QueueItem {
+        bone: first.bone,
+        effector: Point3::from(effector_sum / count),
+        target: Point3::from(target_sum / count),
+        weight: weight_sum / count,
+        falloff: falloff_sum / count,
+    })
 }
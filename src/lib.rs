@@ -35,4 +35,10 @@ pub mod ik;
 
 mod skelly;
 
+#[cfg(feature = "ron")]
+mod schema;
+
 pub use self::skelly::*;
+
+#[cfg(feature = "ron")]
+pub use self::schema::SchemaError;
@@ -0,0 +1,157 @@
+//! This module contains human-editable RON schema for skellies.
+
+use {
+    crate::skelly::{Skelly, SkellyError},
+    na::{Isometry3, RealField, Translation3, UnitQuaternion},
+    ron::extensions::Extensions,
+    serde::Deserialize,
+    std::collections::HashMap,
+};
+
+#[derive(Deserialize)]
+struct SkellySchema {
+    bones: Vec<BoneSchema>,
+}
+
+#[derive(Deserialize)]
+struct BoneSchema {
+    name: String,
+
+    #[serde(default)]
+    parent: Option<String>,
+
+    // offset from the parent.
+    #[serde(default)]
+    offset: [f64; 3],
+
+    // roll, pitch and yaw relative to the parent.
+    #[serde(default)]
+    rotation: [f64; 3],
+}
+
+/// Error loading a skelly from RON schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaError {
+    /// Document does not match the schema.
+    Parse(ron::error::SpannedError),
+
+    /// Two bones have the same name.
+    DuplicateName { bone: String },
+
+    /// Parent of the bone is not defined.
+    UnknownParent { bone: String, parent: String },
+
+    /// Bone is its own ancestor.
+    Cycle { bone: String },
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Parse(err) => write!(f, "Failed to parse skelly schema: {}", err),
+            SchemaError::DuplicateName { bone } => {
+                write!(f, "Bone name '{}' is used more than once", bone)
+            }
+            SchemaError::UnknownParent { bone, parent } => {
+                write!(f, "Parent '{}' of bone '{}' is not defined", parent, bone)
+            }
+            SchemaError::Cycle { bone } => write!(f, "Bone '{}' is its own ancestor", bone),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchemaError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Skelly<T, String>
+where
+    T: RealField + Copy,
+{
+    /// Loads skelly from RON document.
+    ///
+    /// Document lists named bones. Each bone has optional `parent` name,
+    /// `offset` from the parent and `rotation` relative to the parent
+    /// as roll, pitch and yaw angles.\
+    /// Bones without parent are roots and are placed at `offset`.
+    /// Bone names become userdata, parents may be listed after their children.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::Vector3};
+    /// let skelly = Skelly::<f32, String>::from_ron(r#"(
+    ///     bones: [
+    ///         (name: "hips", offset: (0.0, 0.0, 1.0)),
+    ///         (name: "palm", parent: "arm", offset: (0.5, 0.0, 0.0)),
+    ///         (name: "arm", parent: "hips", offset: (0.0, 0.0, 0.5), rotation: (0.0, 0.0, 1.5)),
+    ///     ],
+    /// )"#).unwrap();
+    ///
+    /// let id = |name: &str| (0..skelly.len()).find(|&bone| skelly.get_userdata(bone) == name).unwrap();
+    /// assert_eq!(skelly.get_parent(id("hips")), None);
+    /// assert_eq!(skelly.get_parent(id("arm")), Some(id("hips")));
+    /// assert_eq!(skelly.get_parent(id("palm")), Some(id("arm")));
+    /// assert_eq!(*skelly.get_position(id("hips")), Vector3::new(0.0, 0.0, 1.0));
+    /// assert_eq!(skelly.get_orientation(id("arm")).angle(), 1.5);
+    ///
+    /// assert!(Skelly::<f32, String>::from_ron(r#"(bones: [(name: "arm", parent: "body")])"#).is_err());
+    /// ```
+    pub fn from_ron(s: &str) -> Result<Self, SchemaError> {
+        let options = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        let schema: SkellySchema = options.from_str(s).map_err(SchemaError::Parse)?;
+
+        let mut indices = HashMap::with_capacity(schema.bones.len());
+        for (index, bone) in schema.bones.iter().enumerate() {
+            if indices.insert(bone.name.as_str(), index).is_some() {
+                return Err(SchemaError::DuplicateName {
+                    bone: bone.name.clone(),
+                });
+            }
+        }
+
+        let mut parents = Vec::with_capacity(schema.bones.len());
+        let mut rest = Vec::with_capacity(schema.bones.len());
+        for bone in &schema.bones {
+            let parent = match &bone.parent {
+                None => -1,
+                Some(parent) => match indices.get(parent.as_str()) {
+                    Some(&parent) => parent as i32,
+                    None => {
+                        return Err(SchemaError::UnknownParent {
+                            bone: bone.name.clone(),
+                            parent: parent.clone(),
+                        })
+                    }
+                },
+            };
+            parents.push(parent);
+
+            let [x, y, z] = bone.offset;
+            let [roll, pitch, yaw] = bone.rotation;
+            rest.push(Isometry3::from_parts(
+                Translation3::new(na::convert(x), na::convert(y), na::convert(z)),
+                UnitQuaternion::from_euler_angles(
+                    na::convert(roll),
+                    na::convert(pitch),
+                    na::convert(yaw),
+                ),
+            ));
+        }
+
+        let names = schema.bones.iter().map(|bone| bone.name.clone()).collect();
+        match Skelly::from_parents(&parents, &rest, names) {
+            Ok((skelly, _)) => Ok(skelly),
+            Err(SkellyError::Cycle { bone }) => Err(SchemaError::Cycle {
+                bone: schema.bones[bone].name.clone(),
+            }),
+            // Parents are resolved from names and arrays are built together.
+            Err(err) => unreachable!("{}", err),
+        }
+    }
+}
@@ -1,11 +1,212 @@
-use na::{Isometry3, Point3, RealField, Scalar, Translation3, UnitQuaternion, Vector3};
+use {
+    na::{
+        Isometry3, Matrix3, Matrix4, Point3, Quaternion, RealField, Rotation3, Scalar,
+        Translation3, Unit, UnitQuaternion, Vector3,
+    },
+    std::sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+/// Error building a skelly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SkellyError {
+    /// Lengths of provided arrays do not match.
+    LengthMismatch,
+
+    /// Parent index of the bone is out of bounds.
+    ParentOutOfBounds { bone: usize, parent: usize },
+
+    /// Bone is its own ancestor.
+    Cycle { bone: usize },
+}
+
+impl std::fmt::Display for SkellyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkellyError::LengthMismatch => f.write_str("Lengths of bone arrays do not match"),
+            SkellyError::ParentOutOfBounds { bone, parent } => write!(
+                f,
+                "Parent index {} of bone {} is out of bounds",
+                parent, bone
+            ),
+            SkellyError::Cycle { bone } => write!(f, "Bone {} is its own ancestor", bone),
+        }
+    }
+}
+
+impl std::error::Error for SkellyError {}
+
+/// Coordinate conversion applied to imported rest transformations.
+///
+/// Used with [`Skelly::from_parents_with`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportSettings<T: Scalar> {
+    /// Change of basis from source coordinates to skelly coordinates.\
+    /// Must be orthonormal. Reflections convert handedness.
+    pub axis_conversion: Matrix3<T>,
+
+    /// Uniform scale applied to translations, e.g. to convert units.
+    pub scale: T,
+}
+
+impl<T> Default for ImportSettings<T>
+where
+    T: RealField,
+{
+    fn default() -> Self {
+        ImportSettings {
+            axis_conversion: Matrix3::identity(),
+            scale: T::one(),
+        }
+    }
+}
+
+impl<T> ImportSettings<T>
+where
+    T: RealField,
+{
+    /// Returns settings converting right-handed Y-up coordinates, as in glTF,
+    /// to right-handed Z-up coordinates.
+    pub fn y_up_to_z_up() -> Self {
+        ImportSettings {
+            axis_conversion: Matrix3::new(
+                T::one(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                -T::one(),
+                T::zero(),
+                T::one(),
+                T::zero(),
+            ),
+            scale: T::one(),
+        }
+    }
+
+    /// Converts isometry from source coordinates to skelly coordinates.
+    pub fn convert(&self, isometry: &Isometry3<T>) -> Isometry3<T> {
+        let basis = &self.axis_conversion;
+        let rotation =
+            basis * isometry.rotation.clone().to_rotation_matrix().matrix() * basis.transpose();
+        let translation = basis * &isometry.translation.vector * self.scale.clone();
+
+        Isometry3::from_parts(
+            Translation3::from(translation),
+            UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(rotation)),
+        )
+    }
+}
+
+/// Typed id of a bone.
+///
+/// Opt-in alternative to raw `usize` ids.\
+/// Typed id remembers the skelly that created it,
+/// typed methods of [`Skelly`] panic when given id of another skelly.
+/// Ids stay valid for clones of the skelly.\
+/// [`BoneId::index`] returns raw index for use with index-based methods.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{BoneId, Skelly}, na::{Point3, Vector3}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root: BoneId = skelly.add_root_typed(Point3::origin());
+/// let bone: BoneId = skelly.attach_typed(Vector3::x(), root);
+/// let tip: BoneId = skelly.attach_typed(Vector3::x(), bone);
+///
+/// assert_eq!(skelly.get_parent_typed(tip), Some(bone));
+/// assert_eq!(skelly.get_parent_typed(root), None);
+/// assert_eq!(skelly.get_parent(tip.index()), Some(bone.index()));
+/// assert_eq!(*skelly.get_position(skelly.index_of(bone)), Vector3::x());
+/// assert_eq!(skelly.iter_chain(tip.index()).collect::<Vec<_>>(), [bone.index(), root.index()]);
+///
+/// let other = Skelly::<f32>::new();
+/// let result = std::panic::catch_unwind(|| other.index_of(root));
+/// assert!(result.is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoneId {
+    index: usize,
+    skelly: u64,
+}
+
+impl BoneId {
+    /// Returns raw index of the bone.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl From<BoneId> for usize {
+    fn from(id: BoneId) -> Self {
+        id.index
+    }
+}
+
+/// FNV-1a offset basis used as topology hash of empty skelly.
+const TOPOLOGY_HASH_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Mixes `parent` of the next bone into topology `hash` with FNV-1a.
+fn topology_hash_step(mut hash: u64, parent: Option<usize>) -> u64 {
+    let value = parent.map_or(u64::MAX, |parent| parent as u64);
+    for byte in value.to_le_bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Returns unique revision for new or modified skelly.
+fn next_revision() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns unique tag for new skelly.
+fn next_skelly_tag() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
 
 /// One's skeleton.
 /// Parameterized with numric value and bone userdata type.
+///
+/// Skellies may be compared structurally when numeric value
+/// and userdata types can be compared.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::Skelly, na::{Point3, Vector3}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let bone = skelly.attach(Vector3::x(), root);
+///
+/// let mut copy = skelly.clone();
+/// assert_eq!(copy, skelly);
+///
+/// copy.set_position(bone, Vector3::y());
+/// assert_ne!(copy, skelly);
+/// ```
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Skelly<T: Scalar, D = ()> {
     bones: Vec<Bone<T, D>>,
+
+    // identifies skelly for typed bone ids.
+    #[cfg_attr(feature = "serde-1", serde(skip, default = "next_skelly_tag"))]
+    tag: u64,
+
+    // changes on every modification of bones.
+    #[cfg_attr(feature = "serde-1", serde(skip, default = "next_revision"))]
+    revision: u64,
+
+    // memoized topology hash. updated when bones are added or removed.
+    #[cfg_attr(feature = "serde-1", serde(skip, default = "OnceLock::new"))]
+    topology: OnceLock<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -14,6 +215,32 @@ struct Bone<T: Scalar, D> {
     isometry: Isometry3<T>,
     parent: Option<usize>,
     userdata: D,
+
+    // memoized global isometry. reset when bone or its ancestors change.
+    #[cfg_attr(feature = "serde-1", serde(skip, default = "OnceLock::new"))]
+    global: OnceLock<Isometry3<T>>,
+}
+
+impl<T, D> PartialEq for Skelly<T, D>
+where
+    T: RealField,
+    D: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.bones == other.bones
+    }
+}
+
+impl<T, D> PartialEq for Bone<T, D>
+where
+    T: RealField,
+    D: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.isometry == other.isometry
+            && self.parent == other.parent
+            && self.userdata == other.userdata
+    }
 }
 
 impl<T, D> Default for Skelly<T, D>
@@ -38,7 +265,196 @@ where
     /// let skelly = Skelly::<f32>::new();
     /// ```
     pub fn new() -> Self {
-        Skelly { bones: Vec::new() }
+        Skelly {
+            bones: Vec::new(),
+            tag: next_skelly_tag(),
+            revision: next_revision(),
+            topology: OnceLock::new(),
+        }
+    }
+
+    /// Builds skelly from flat array of `parents` with rest isometries
+    /// and userdata of each bone.
+    ///
+    /// Negative parent index denotes root bone.\
+    /// Bones are reordered if a parent follows its child,
+    /// bone ids then differ from indices in `parents`.\
+    /// Also returns mapping from indices in `parents` to bone ids of the skelly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// let mut expected = Skelly::<f32, &str>::new();
+    /// let root = expected.add_root_with(Point3::origin(), "root");
+    /// let left = expected.attach_with(-Vector3::x(), root, "left");
+    /// let right = expected.attach_with(Vector3::x(), root, "right");
+    /// let palm = expected.attach_with(Vector3::x(), right, "palm");
+    ///
+    /// let rest = [
+    ///     Isometry3::identity(),
+    ///     Isometry3::translation(-1.0, 0.0, 0.0),
+    ///     Isometry3::translation(1.0, 0.0, 0.0),
+    ///     Isometry3::translation(1.0, 0.0, 0.0),
+    /// ];
+    /// let (skelly, mapping) = Skelly::from_parents(&[-1, 0, 0, 2], &rest, vec!["root", "left", "right", "palm"]).unwrap();
+    /// assert_eq!(skelly, expected);
+    /// assert_eq!(mapping, [0, 1, 2, 3]);
+    ///
+    /// // Parent follows its child.
+    /// let (reordered, mapping) = Skelly::<f32, &str>::from_parents(&[1, -1], &rest[..2], vec!["child", "root"]).unwrap();
+    /// assert_eq!(mapping, [1, 0]);
+    /// assert_eq!(*reordered.get_userdata(mapping[0]), "child");
+    /// assert_eq!(*reordered.get_userdata(mapping[1]), "root");
+    /// assert_eq!(reordered.get_parent(mapping[0]), Some(mapping[1]));
+    ///
+    /// assert!(Skelly::<f32, ()>::from_parents(&[-1, 0], &rest, vec![(), ()]).is_err());
+    /// assert!(Skelly::<f32, ()>::from_parents(&[1, 0], &rest[..2], vec![(), ()]).is_err());
+    /// ```
+    pub fn from_parents(
+        parents: &[i32],
+        rest: &[Isometry3<T>],
+        userdata: Vec<D>,
+    ) -> Result<(Self, Vec<usize>), SkellyError> {
+        if parents.len() != rest.len() || parents.len() != userdata.len() {
+            return Err(SkellyError::LengthMismatch);
+        }
+
+        let parent_of = |bone: usize| -> Result<Option<usize>, SkellyError> {
+            if parents[bone] < 0 {
+                return Ok(None);
+            }
+            let parent = parents[bone] as usize;
+            if parent < parents.len() {
+                Ok(Some(parent))
+            } else {
+                Err(SkellyError::ParentOutOfBounds { bone, parent })
+            }
+        };
+
+        // Order bones so that each parent precedes its children.
+        let mut order = Vec::with_capacity(parents.len());
+        let mut mapping = vec![usize::MAX; parents.len()];
+        let mut visiting = vec![false; parents.len()];
+        let mut stack = Vec::new();
+        for bone in 0..parents.len() {
+            let mut next = Some(bone);
+            while let Some(bone) = next {
+                if mapping[bone] != usize::MAX {
+                    break;
+                }
+                if visiting[bone] {
+                    return Err(SkellyError::Cycle { bone });
+                }
+                visiting[bone] = true;
+                stack.push(bone);
+                next = parent_of(bone)?;
+            }
+            while let Some(bone) = stack.pop() {
+                mapping[bone] = order.len();
+                order.push(bone);
+            }
+        }
+
+        let mut userdata: Vec<_> = userdata.into_iter().map(Some).collect();
+        let mut bones = Vec::with_capacity(parents.len());
+        for bone in order {
+            bones.push(Bone {
+                isometry: rest[bone].clone(),
+                parent: parent_of(bone)?.map(|parent| mapping[parent]),
+                userdata: userdata[bone].take().unwrap(),
+                global: OnceLock::new(),
+            });
+        }
+
+        let skelly = Skelly {
+            bones,
+            tag: next_skelly_tag(),
+            revision: next_revision(),
+            topology: OnceLock::new(),
+        };
+        Ok((skelly, mapping))
+    }
+
+    /// Builds skelly from flat array of `parents` with rest isometries
+    /// and userdata of each bone, converting rest isometries
+    /// with `settings`.
+    ///
+    /// See [`Skelly::from_parents`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{ImportSettings, Skelly}, na::{Isometry3, Point3, UnitQuaternion}};
+    /// // Y-up spine in centimeters.
+    /// let rest = [
+    ///     Isometry3::translation(0.0, 100.0, 0.0),
+    ///     Isometry3::translation(0.0, 50.0, 0.0),
+    ///     Isometry3::from_parts(
+    ///         [0.0, 50.0, 0.0].into(),
+    ///         UnitQuaternion::from_euler_angles(0.0, std::f32::consts::FRAC_PI_2, 0.0),
+    ///     ),
+    ///     Isometry3::translation(20.0, 0.0, 0.0),
+    /// ];
+    /// let settings = ImportSettings { scale: 0.01, ..ImportSettings::y_up_to_z_up() };
+    /// let (skelly, _) = Skelly::<f32, ()>::from_parents_with(&[-1, 0, 1, 2], &rest, vec![(); 4], &settings).unwrap();
+    ///
+    /// let mut globals = vec![Isometry3::identity(); skelly.len()];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    ///
+    /// // Spine is along Z.
+    /// let neck = globals[2].translation.vector;
+    /// assert!((neck - na::Vector3::new(0.0, 0.0, 2.0)).norm() < 1e-5);
+    ///
+    /// // Arm rotated about up axis in source is rotated about Z axis.
+    /// let palm = globals[3] * Point3::origin();
+    /// assert!((palm.coords - na::Vector3::new(0.0, 0.2, 2.0)).norm() < 1e-5);
+    /// ```
+    pub fn from_parents_with(
+        parents: &[i32],
+        rest: &[Isometry3<T>],
+        userdata: Vec<D>,
+        settings: &ImportSettings<T>,
+    ) -> Result<(Self, Vec<usize>), SkellyError>
+    where
+        T: RealField,
+    {
+        let rest: Vec<_> = rest
+            .iter()
+            .map(|isometry| settings.convert(isometry))
+            .collect();
+        Self::from_parents(parents, &rest, userdata)
+    }
+
+    /// Returns flat array of parent indices, `-1` for root bones.
+    ///
+    /// This is the counterpart of [`Skelly::from_parents`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32, &str>::new();
+    /// let root = skelly.add_root_with(Point3::origin(), "root");
+    /// let left = skelly.attach_with(-Vector3::x(), root, "left");
+    /// let right = skelly.attach_with(Vector3::x(), root, "right");
+    /// skelly.attach_with(Vector3::x(), right, "palm");
+    /// skelly.attach_with(Vector3::z(), left, "thumb");
+    ///
+    /// let parents = skelly.parents();
+    /// assert_eq!(parents, [-1, 0, 0, 2, 1]);
+    ///
+    /// let rest: Vec<_> = (0..skelly.len()).map(|bone| *skelly.get_isometry(bone)).collect();
+    /// let userdata = (0..skelly.len()).map(|bone| *skelly.get_userdata(bone)).collect();
+    /// let (copy, _) = Skelly::from_parents(&parents, &rest, userdata).unwrap();
+    /// assert_eq!(copy, skelly);
+    /// assert_eq!(copy.topology_hash(), skelly.topology_hash());
+    /// ```
+    pub fn parents(&self) -> Vec<i32> {
+        self.bones
+            .iter()
+            .map(|bone| bone.parent.map_or(-1, |parent| parent as i32))
+            .collect()
     }
 
     /// Creates new root bone in the skelly at specified `position`.
@@ -68,7 +484,9 @@ where
             },
             parent: None,
             userdata,
+            global: OnceLock::new(),
         });
+        self.extend_topology(None);
         self.bones.len() - 1
     }
 
@@ -106,11 +524,70 @@ where
             },
             parent: Some(parent),
             userdata,
+            global: OnceLock::new(),
         });
+        self.extend_topology(Some(parent));
 
         self.bones.len() - 1
     }
 
+    /// Attaches new bone to an existing bone with specified id
+    /// placing it at `global_tip` in global space.
+    ///
+    /// Relative position is computed from the `parent`'s global isometry
+    /// in current skelly transformations placed at `skelly_global`.\
+    /// `userdata` will be associated with the bone.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `parent` index is out of bounds.
+    #[track_caller]
+    pub fn attach_at_global_with(
+        &mut self,
+        global_tip: Point3<T>,
+        parent: usize,
+        skelly_global: &Isometry3<T>,
+        userdata: D,
+    ) -> usize
+    where
+        T: RealField,
+    {
+        assert!(parent < self.bones.len(), "Parent index is ouf of bounds");
+        let parent_global = self.global_of(parent, skelly_global);
+        let relative = parent_global.inverse_transform_point(&global_tip).coords;
+        self.attach_with(relative, parent, userdata)
+    }
+
+    /// Attaches new bone to the most recently added bone.
+    ///
+    /// Returns id of the added bone.\
+    /// The bone will be placed `relative` to its parent.\
+    /// `userdata` will be associated with the bone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32, &str>::new();
+    /// let root = skelly.add_root_with(Point3::origin(), "root");
+    /// let bone = skelly.extend_with(Vector3::x(), "bone");
+    /// let tip = skelly.extend_with(Vector3::x(), "tip");
+    ///
+    /// assert_eq!(skelly.get_parent(tip), Some(bone));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if skelly is empty.
+    #[track_caller]
+    pub fn extend_with(&mut self, relative: Vector3<T>, userdata: D) -> usize
+    where
+        T: RealField,
+    {
+        assert!(!self.bones.is_empty(), "Cannot extend empty skelly");
+        self.attach_with(relative, self.bones.len() - 1, userdata)
+    }
+
     /// Rotates bone with specified id.
     ///
     /// *Does not* affect relative position to the parent and global position for root bones.
@@ -150,7 +627,8 @@ where
     where
         T: RealField,
     {
-        self.bones[bone].isometry.rotation *= rotation
+        self.bones[bone].isometry.rotation *= rotation;
+        self.invalidate_globals(bone);
     }
 
     /// Rotates bone with specified id.
@@ -194,6 +672,7 @@ where
     {
         let my_isometry = &mut self.bones[bone].isometry;
         *my_isometry = rotation * &*my_isometry;
+        self.invalidate_globals(bone);
     }
 
     /// Translates bone with specified id.
@@ -236,6 +715,7 @@ where
         T: RealField,
     {
         self.bones[bone].isometry.translation *= translation;
+        self.invalidate_globals(bone);
     }
 
     /// Sets relative position for bone with specified id.
@@ -275,11 +755,66 @@ where
     #[track_caller]
     pub fn set_position(&mut self, bone: usize, position: Vector3<T>) {
         self.bones[bone].isometry.translation = position.into();
+        self.invalidate_globals(bone);
+    }
+
+    /// Sets relative position for bone with specified id
+    /// keeping global isometries of all descendant bones.
+    ///
+    /// Relative positions of direct children are compensated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::consts::PI};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let left = skelly.attach(Vector3::x(), bone);
+    /// let right = skelly.attach(Vector3::y(), bone);
+    /// skelly.set_orientation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    ///
+    /// let mut globals_old = [Isometry3::identity(); 4];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_old);
+    ///
+    /// skelly.set_position_keep_children(bone, Vector3::z());
+    ///
+    /// let mut globals_new = [Isometry3::identity(); 4];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_new);
+    ///
+    /// assert!((globals_new[bone].translation.vector - Vector3::z()).magnitude() < 1e-5);
+    /// for child in [left, right] {
+    ///     let old = globals_old[child].translation.vector;
+    ///     let new = globals_new[child].translation.vector;
+    ///     assert!((old - new).magnitude() < 1e-5);
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn set_position_keep_children(&mut self, bone: usize, position: Vector3<T>)
+    where
+        T: RealField,
+    {
+        let isometry = &mut self.bones[bone].isometry;
+        let shift = isometry
+            .rotation
+            .inverse_transform_vector(&(&isometry.translation.vector - &position));
+        isometry.translation = position.into();
+
+        for child in &mut self.bones[bone + 1..] {
+            if child.parent == Some(bone) {
+                child.isometry.translation.vector += &shift;
+            }
+        }
+        self.invalidate_globals(bone);
     }
 
     /// Returns current bone position relative to parent.
     #[track_caller]
-    pub fn get_position(&mut self, bone: usize) -> &Vector3<T>
+    pub fn get_position(&self, bone: usize) -> &Vector3<T>
     where
         T: RealField,
     {
@@ -323,11 +858,12 @@ where
     #[track_caller]
     pub fn set_orientation(&mut self, bone: usize, orientation: UnitQuaternion<T>) {
         self.bones[bone].isometry.rotation = orientation;
+        self.invalidate_globals(bone);
     }
 
     /// Returns current bone orientation relative to parent.
     #[track_caller]
-    pub fn get_orientation(&mut self, bone: usize) -> &UnitQuaternion<T>
+    pub fn get_orientation(&self, bone: usize) -> &UnitQuaternion<T>
     where
         T: RealField,
     {
@@ -336,7 +872,7 @@ where
 
     /// Returns current bone isometry relative to parent.
     #[track_caller]
-    pub fn get_isometry(&mut self, bone: usize) -> &Isometry3<T>
+    pub fn get_isometry(&self, bone: usize) -> &Isometry3<T>
     where
         T: RealField,
     {
@@ -407,6 +943,79 @@ where
         self.bones[bone].userdata = userdata
     }
 
+    /// Calls `f` for each bone with its id,
+    /// mutable relative isometry and mutable userdata.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3, Translation3}};
+    /// let mut skelly = Skelly::<f32, usize>::new();
+    /// let root = skelly.add_root_with(Point3::origin(), 0);
+    /// let bone = skelly.attach_with(Vector3::x(), root, 0);
+    /// let tip = skelly.attach_with(Vector3::x(), bone, 0);
+    ///
+    /// let mut globals_old = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_old);
+    ///
+    /// skelly.for_each_bone_mut(|index, isometry, userdata| {
+    ///     isometry.append_translation_mut(&Translation3::new(0.0, 0.0, 1.0));
+    ///     *userdata = index;
+    /// });
+    ///
+    /// let mut globals_new = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_new);
+    ///
+    /// // Each bone is shifted by its own offset and offsets of all its ancestors.
+    /// for (depth, (old, new)) in globals_old.iter().zip(&globals_new).enumerate() {
+    ///     let shift = new.translation.vector - old.translation.vector;
+    ///     assert_eq!(shift, Vector3::z() * (depth + 1) as f32);
+    /// }
+    /// assert_eq!(*skelly.get_userdata(tip), tip);
+    /// ```
+    pub fn for_each_bone_mut(&mut self, mut f: impl FnMut(usize, &mut Isometry3<T>, &mut D)) {
+        for (index, bone) in self.bones.iter_mut().enumerate() {
+            f(index, &mut bone.isometry, &mut bone.userdata);
+        }
+        self.invalidate_all_globals();
+    }
+
+    /// Returns read-only view of the `bone`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32, &str>::new();
+    /// let root = skelly.add_root_with(Point3::new(0.0, 0.0, 1.0), "root");
+    /// let bone = skelly.attach_with(Vector3::x(), root, "bone");
+    /// let tip = skelly.attach_with(Vector3::x(), bone, "tip");
+    /// skelly.set_orientation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5));
+    ///
+    /// let skelly_global = Isometry3::translation(1.0, 2.0, 3.0);
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&skelly_global, &mut globals);
+    ///
+    /// let view = skelly.bone(tip);
+    /// assert_eq!(view.id(), tip);
+    /// assert_eq!(view.parent(), Some(bone));
+    /// assert_eq!(*view.userdata(), "tip");
+    /// assert_eq!(view.local().translation.vector, Vector3::x());
+    ///
+    /// let global = view.global(&skelly_global);
+    /// assert!((global.translation.vector - globals[tip].translation.vector).magnitude() < 1e-5);
+    /// assert!(global.rotation.angle_to(&globals[tip].rotation) < 1e-5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn bone(&self, bone: usize) -> BoneView<'_, T, D> {
+        assert!(bone < self.bones.len(), "Bone index is out of bounds");
+        BoneView { skelly: self, bone }
+    }
+
     /// Returns parent of the specified `bone`.
     /// Returns `None` for root bones.
     ///
@@ -429,6 +1038,33 @@ where
         self.bones[bone].parent
     }
 
+    /// Returns typed id of the parent of the bone with specified typed id.
+    ///
+    /// Same as [`Skelly::get_parent`] but uses typed ids.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` is id of another skelly.
+    #[track_caller]
+    pub fn get_parent_typed(&self, bone: BoneId) -> Option<BoneId> {
+        let parent = self.get_parent(self.index_of(bone))?;
+        Some(BoneId {
+            index: parent,
+            skelly: self.tag,
+        })
+    }
+
+    /// Returns raw index of the bone with specified typed id.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` is id of another skelly.
+    #[track_caller]
+    pub fn index_of(&self, bone: BoneId) -> usize {
+        assert_eq!(bone.skelly, self.tag, "Bone id belongs to another skelly");
+        bone.index
+    }
+
     /// Returns number of bones in the skelly.
     ///
     /// # Example
@@ -463,55 +1099,129 @@ where
         self.bones.is_empty()
     }
 
-    /// Fills slice of `Isometry3` with global isometries
-    /// for each bone of the skelly.
+    /// Returns number of bones the skelly can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.bones.capacity()
+    }
+
+    /// Removes all bones from the skelly.
+    ///
+    /// Allocated memory is kept for reuse.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
+    /// let mut posture = Posture::new(&skelly);
     ///
-    /// let mut globals = [Isometry3::identity(); 2];
-    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    /// let capacity = skelly.capacity();
+    /// skelly.clear();
+    /// posture.clear();
+    ///
+    /// assert!(skelly.is_empty());
+    /// assert_eq!(skelly.capacity(), capacity);
+    /// assert!(posture.is_compatible(&skelly));
     /// ```
-    pub fn write_globals(&self, skelly_global: &Isometry3<T>, globals: &mut [Isometry3<T>])
-    where
-        T: RealField,
-    {
-        self.bones
-            .iter()
-            .take(globals.len())
-            .enumerate()
-            .for_each(|(index, bone)| match bone.parent {
-                Some(parent) => {
-                    debug_assert!(parent < index);
-                    globals[index] = &globals[parent] * &bone.isometry;
-                }
-                None => {
-                    globals[index] = skelly_global * &bone.isometry;
-                }
-            })
+    pub fn clear(&mut self) {
+        self.bones.clear();
+        self.topology = OnceLock::new();
+        self.revision = next_revision();
     }
 
-    /// Makes the skelly to assume specifed posture.
-    #[track_caller]
-    pub fn assume_posture(&mut self, posture: &Posture<T>)
-    where
-        T: Copy,
-    {
-        assert_eq!(self.bones.len(), posture.joints.len());
-
-        self.bones
-            .iter_mut()
-            .zip(&posture.joints)
-            .for_each(|(bone, isometry)| bone.isometry = *isometry);
+    /// Returns number of bones at each depth level.
+    ///
+    /// Root bones have depth zero.
+    /// Wide skellies have more bones per level, deep skellies have more levels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let left = skelly.attach(Vector3::x(), root);
+    /// let right = skelly.attach(-Vector3::x(), root);
+    /// skelly.attach(Vector3::x(), left);
+    /// skelly.attach(-Vector3::x(), right);
+    /// let tip = skelly.attach(-Vector3::x(), right);
+    /// skelly.attach(-Vector3::x(), tip);
+    /// skelly.add_root(Point3::origin());
+    ///
+    /// assert_eq!(skelly.depth_histogram(), [2, 2, 3, 1]);
+    /// ```
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        let mut depths = Vec::with_capacity(self.bones.len());
+        let mut histogram = Vec::new();
+
+        for bone in &self.bones {
+            let depth = match bone.parent {
+                Some(parent) => depths[parent] + 1,
+                None => 0,
+            };
+            depths.push(depth);
+
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+        }
+
+        histogram
     }
 
-    /// Iterates through bone ancestors up until root bone is reached
-    /// yielding their ids.
+    /// Returns hash of the skelly hierarchy.
+    ///
+    /// Skellies with the same parent for each bone have equal hashes.\
+    /// Hash is memoized and does not depend on Rust version or platform.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let single = skelly.topology_hash();
+    ///
+    /// skelly.attach(Vector3::x(), root);
+    /// assert_ne!(skelly.topology_hash(), single);
+    ///
+    /// let mut other = Skelly::<f32>::new();
+    /// let root = other.add_root(Point3::origin());
+    /// other.attach(Vector3::y(), root);
+    /// assert_eq!(skelly.topology_hash(), other.topology_hash());
+    /// ```
+    pub fn topology_hash(&self) -> u64 {
+        *self.topology.get_or_init(|| {
+            self.bones.iter().fold(TOPOLOGY_HASH_BASIS, |hash, bone| {
+                topology_hash_step(hash, bone.parent)
+            })
+        })
+    }
+
+    /// Returns revision of the skelly.
+    ///
+    /// Revision changes whenever bones are added or removed
+    /// or their transformations are modified
+    /// and is never shared by skellies with different bones,
+    /// except for clones that were not modified since cloning.
+    #[cfg(feature = "ik")]
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Updates memoized topology hash with newly added bone.
+    fn extend_topology(&mut self, parent: Option<usize>) {
+        self.revision = next_revision();
+        if let Some(hash) = self.topology.get_mut() {
+            *hash = topology_hash_step(*hash, parent);
+        }
+    }
+
+    /// Fills slice of `Isometry3` with global isometries
+    /// for each bone of the skelly.
     ///
     /// # Example
     ///
@@ -520,169 +1230,1596 @@ where
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
-    /// let tip = skelly.attach(Vector3::x(), bone);
     ///
-    /// assert_eq!(skelly.iter_chain(tip).collect::<Vec<_>>(), [bone, root]);
+    /// let mut globals = [Isometry3::identity(); 2];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In debug builds panics if a bone is placed before its parent,
+    /// which may happen only with skelly deserialized from malformed data.\
+    /// Release builds do not check this and silently compute global isometry of such bone
+    /// from the stale value `globals` holds for its parent.
+    ///
+    #[cfg_attr(
+        all(feature = "serde-1", feature = "ron", debug_assertions),
+        doc = "```"
+    )]
+    #[cfg_attr(
+        not(all(feature = "serde-1", feature = "ron", debug_assertions)),
+        doc = "```ignore"
+    )]
+    /// # use {skelly::Skelly, na::Isometry3, std::panic::{catch_unwind, AssertUnwindSafe}};
+    /// // Root is placed after its child.
+    /// let skelly: Skelly<f32> = ron::from_str(
+    ///     "(bones: [
+    ///         (isometry: (rotation: [0.0, 0.0, 0.0, 1.0], translation: [1.0, 0.0, 0.0]), parent: Some(1), userdata: ()),
+    ///         (isometry: (rotation: [0.0, 0.0, 0.0, 1.0], translation: [0.0, 0.0, 0.0]), parent: None, userdata: ()),
+    ///     ])",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut globals = [Isometry3::identity(); 2];
+    /// let panic = catch_unwind(AssertUnwindSafe(|| {
+    ///     skelly.write_globals(&Isometry3::identity(), &mut globals)
+    /// }))
+    /// .unwrap_err();
+    ///
+    /// let message = panic.downcast_ref::<String>().unwrap();
+    /// assert!(message.contains("is placed before its parent"));
+    /// ```
+    pub fn write_globals(&self, skelly_global: &Isometry3<T>, globals: &mut [Isometry3<T>])
+    where
+        T: RealField,
+    {
+        self.bones
+            .iter()
+            .take(globals.len())
+            .enumerate()
+            .for_each(|(index, bone)| match bone.parent {
+                Some(parent) => {
+                    debug_assert!(
+                        parent < index,
+                        "Bone {} is placed before its parent {}. Global isometry of the parent is not computed yet",
+                        index,
+                        parent,
+                    );
+                    globals[index] = &globals[parent] * &bone.isometry;
+                }
+                None => {
+                    globals[index] = skelly_global * &bone.isometry;
+                }
+            })
+    }
+
+    /// Transforms all root bones by specified isometry,
+    /// moving the whole skelly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let other_root = skelly.add_root(Point3::new(0.0, 0.0, 1.0));
+    ///
+    /// let by = Isometry3::from_parts(
+    ///     Vector3::y().into(),
+    ///     UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0),
+    /// );
+    ///
+    /// let mut globals_old = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_old);
+    ///
+    /// skelly.transform_roots(&by);
+    ///
+    /// let mut globals_new = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals_new);
+    ///
+    /// for (old, new) in globals_old.iter().zip(&globals_new) {
+    ///     let expected = by * old;
+    ///     assert!((new.translation.vector - expected.translation.vector).magnitude() < EPSILON);
+    /// }
+    /// ```
+    pub fn transform_roots(&mut self, by: &Isometry3<T>)
+    where
+        T: RealField,
+    {
+        for bone in &mut self.bones {
+            if bone.parent.is_none() {
+                bone.isometry = by * &bone.isometry;
+            }
+        }
+        self.invalidate_all_globals();
+    }
+
+    /// Rotates whole skelly about the origin so that rest direction
+    /// of the `reference_bone` from its parent points along the `axis`.
+    ///
+    /// Does nothing if the `reference_bone` coincides with its parent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::new(1.0, 0.0, 0.0));
+    /// let spine = skelly.attach(Vector3::new(0.3, 0.2, 0.9), root);
+    /// let neck = Vector3::new(0.1, -0.2, 0.4);
+    /// let head = skelly.attach(neck, spine);
+    ///
+    /// skelly.align_to_axis(spine, Vector3::z_axis());
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    /// let direction = (globals[spine].translation.vector - globals[root].translation.vector).normalize();
+    /// assert!((direction - Vector3::z()).magnitude() < 1e-5);
+    ///
+    /// // Lengths are preserved.
+    /// let length = (globals[head].translation.vector - globals[spine].translation.vector).magnitude();
+    /// assert!((length - neck.magnitude()).abs() < 1e-5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `reference_bone` index is out of bounds
+    /// or `reference_bone` is a root.
+    #[track_caller]
+    pub fn align_to_axis(&mut self, reference_bone: usize, axis: Unit<Vector3<T>>)
+    where
+        T: RealField + Copy,
+    {
+        let parent = self.bones[reference_bone]
+            .parent
+            .expect("Reference bone must have a parent");
+
+        let direction = self.cached_global(reference_bone).translation.vector
+            - self.cached_global(parent).translation.vector;
+
+        let direction = match Unit::try_new(direction, T::default_epsilon()) {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let rotation =
+            UnitQuaternion::rotation_between_axis(&direction, &axis).unwrap_or_else(|| {
+                // Direction is opposite to the axis. Turn around any perpendicular axis.
+                let perpendicular =
+                    Unit::try_new(direction.cross(&Vector3::x()), T::default_epsilon())
+                        .unwrap_or_else(|| Unit::new_normalize(direction.cross(&Vector3::y())));
+                UnitQuaternion::from_axis_angle(&perpendicular, T::pi())
+            });
+
+        self.transform_roots(&Isometry3::from_parts(Translation3::identity(), rotation));
+    }
+
+    /// Validates skinning weights stored in bones userdata.
+    ///
+    /// `f` extracts list of `(bone, weight)` pairs from userdata.
+    /// Each list must reference existing bones only
+    /// and its weights must sum to one.
+    ///
+    /// Returns ids of bones with invalid weights lists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32, Vec<(usize, f32)>>::new();
+    /// let root = skelly.add_root_with(Point3::origin(), vec![(0, 1.0)]);
+    /// let bone = skelly.attach_with(Vector3::x(), root, vec![(0, 0.5), (1, 0.5)]);
+    /// assert_eq!(skelly.validate_weights(|weights| weights), Ok(()));
+    ///
+    /// let unnormalized = skelly.attach_with(Vector3::x(), bone, vec![(1, 0.5), (2, 0.25)]);
+    /// let out_of_bounds = skelly.attach_with(Vector3::x(), bone, vec![(5, 1.0)]);
+    /// assert_eq!(
+    ///     skelly.validate_weights(|weights| weights),
+    ///     Err(vec![unnormalized, out_of_bounds]),
+    /// );
+    /// ```
+    pub fn validate_weights<F>(&self, f: F) -> Result<(), Vec<usize>>
+    where
+        T: RealField,
+        F: Fn(&D) -> &[(usize, T)],
+    {
+        let tolerance: T = na::convert(1.0e-4);
+
+        let invalid: Vec<usize> = self
+            .bones
+            .iter()
+            .enumerate()
+            .filter_map(|(index, bone)| {
+                let weights = f(&bone.userdata);
+                let mut sum = T::zero();
+                for (target, weight) in weights {
+                    if *target >= self.bones.len() {
+                        return Some(index);
+                    }
+                    sum += weight.clone();
+                }
+
+                if (sum - T::one()).abs() > tolerance {
+                    Some(index)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Fills slice of `Matrix4` with skinning matrices
+    /// for each bone of the skelly in rest pose.
+    ///
+    /// Skinning matrix is bone's global isometry multiplied by its inverse bind isometry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, Isometry3, Matrix4}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let inverse_binds = [Isometry3::identity(), Isometry3::translation(-1.0, 0.0, 0.0)];
+    /// let skelly_global = Isometry3::translation(0.0, 0.0, 1.0);
+    ///
+    /// let mut rest = [Matrix4::identity(); 2];
+    /// skelly.rest_skinning_matrices(&skelly_global, &inverse_binds, &mut rest);
+    ///
+    /// let mut posed = [Matrix4::identity(); 2];
+    /// Posture::new(&skelly).skinning_matrices(&skelly, &skelly_global, &inverse_binds, &mut posed);
+    ///
+    /// assert_eq!(rest, posed);
+    /// assert_eq!(rest[bone], skelly_global.to_homogeneous());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inverse_binds` length does not match number of bones.
+    #[track_caller]
+    pub fn rest_skinning_matrices(
+        &self,
+        skelly_global: &Isometry3<T>,
+        inverse_binds: &[Isometry3<T>],
+        out: &mut [Matrix4<T>],
+    ) where
+        T: RealField,
+    {
+        assert_eq!(
+            inverse_binds.len(),
+            self.bones.len(),
+            "Inverse binds are not compatible with the skelly"
+        );
+
+        write_skinning_matrices(
+            self.bones.iter().map(|bone| (&bone.isometry, bone.parent)),
+            skelly_global,
+            inverse_binds,
+            out,
+        )
+    }
+
+    /// Returns angle at the `bone` joint in specified `posture`
+    /// between the vector from `bone`'s parent to the `bone`
+    /// and the vector from the `bone` to its `child`, in global space.
+    ///
+    /// For root bones vector from the origin is used instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3}, core::f32::consts::PI};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let elbow = skelly.attach(Vector3::x(), root);
+    /// let palm = skelly.attach(Vector3::x(), elbow);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert!(skelly.joint_angle(&posture, elbow, palm).abs() < 1e-5);
+    ///
+    /// posture.set_position(palm, Vector3::y());
+    /// assert!((skelly.joint_angle(&posture, elbow, palm) - PI / 2.0).abs() < 1e-5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds,
+    /// `child` is not a child of the `bone`
+    /// or `posture` is not compatible with the skelly.
+    #[track_caller]
+    pub fn joint_angle(&self, posture: &Posture<T>, bone: usize, child: usize) -> T
+    where
+        T: RealField,
+    {
+        assert!(
+            posture.is_compatible(self),
+            "Posture is not compatible with the skelly"
+        );
+        assert_eq!(
+            self.bones[child].parent,
+            Some(bone),
+            "Bone is not a parent of the child"
+        );
+
+        let parent_global = posture.parent_global(bone, self);
+        let bone_global = &parent_global * &posture.joints[bone];
+        let child_global = &bone_global * &posture.joints[child];
+
+        let incoming = &bone_global.translation.vector - &parent_global.translation.vector;
+        let outgoing = &child_global.translation.vector - &bone_global.translation.vector;
+        incoming.angle(&outgoing)
+    }
+
+    /// Makes the skelly to assume specifed posture.
+    #[track_caller]
+    pub fn assume_posture(&mut self, posture: &Posture<T>)
+    where
+        T: Copy,
+    {
+        assert_eq!(self.bones.len(), posture.joints.len());
+
+        self.bones
+            .iter_mut()
+            .zip(&posture.joints)
+            .for_each(|(bone, isometry)| bone.isometry = *isometry);
+        self.invalidate_all_globals();
+    }
+
+    /// Writes `src_posture` of another skelly into `out` posture of this skelly
+    /// through bone `map` of source and destination bone pairs.
+    ///
+    /// Mapped joints copy rotation of the source joint
+    /// and keep rest translation of this skelly, preserving its proportions.
+    /// Unmapped joints are left intact.\
+    /// Does not allocate, so may be used to retarget every frame.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut source = Skelly::<f32>::new();
+    /// let hips = source.add_root(Point3::new(0.0, 0.0, 1.0));
+    /// let spine = source.attach(Vector3::z(), hips);
+    /// let head = source.attach(Vector3::z(), spine);
+    ///
+    /// let mut target = Skelly::<f32>::new();
+    /// let pelvis = target.add_root(Point3::new(0.0, 0.0, 2.0));
+    /// let chest = target.attach(Vector3::new(0.0, 0.0, 1.5), pelvis);
+    /// let neck = target.attach(Vector3::new(0.0, 0.0, 0.2), chest);
+    /// let skull = target.attach(Vector3::new(0.0, 0.0, 0.3), neck);
+    ///
+    /// let bend = UnitQuaternion::from_euler_angles(0.4, 0.0, 0.0);
+    /// let mut src_posture = Posture::new(&source);
+    /// src_posture.set_orientation(spine, bend);
+    /// src_posture.set_position(hips, Vector3::new(5.0, 0.0, 1.0));
+    ///
+    /// let mut out = Posture::new(&target);
+    /// target.retarget_into(&src_posture, &[(hips, pelvis), (spine, chest), (head, skull)], &mut out);
+    ///
+    /// assert_eq!(*out.get_orientation(chest), bend);
+    /// assert_eq!(*out.get_position(chest), Vector3::new(0.0, 0.0, 1.5));
+    /// assert_eq!(*out.get_position(pelvis), Vector3::new(0.0, 0.0, 2.0));
+    /// assert_eq!(*out.get_orientation(neck), UnitQuaternion::identity());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not compatible with this skelly
+    /// or any bone index in `map` is out of bounds.
+    #[track_caller]
+    pub fn retarget_into(
+        &self,
+        src_posture: &Posture<T>,
+        map: &[(usize, usize)],
+        out: &mut Posture<T>,
+    ) where
+        T: RealField + Copy,
+    {
+        assert!(
+            out.is_compatible(self),
+            "Posture is not compatible with the skelly"
+        );
+
+        for &(src, dst) in map {
+            let rotation = src_posture.joints[src].rotation;
+            out.joints[dst] = Isometry3::from_parts(self.bones[dst].isometry.translation, rotation);
+        }
+        out.dirty = true;
+    }
+
+    /// Returns isometry of the `bone` relative to its `ancestor`
+    /// in current skelly transformations.
+    ///
+    /// Returns `None` if `ancestor` is not an ancestor of the `bone`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let pelvis = skelly.add_root(Point3::new(0.0, 0.0, 1.0));
+    /// let spine = skelly.attach(Vector3::z(), pelvis);
+    /// let shoulder = skelly.attach(Vector3::z(), spine);
+    /// let arm = skelly.attach(Vector3::x(), shoulder);
+    /// let hand = skelly.attach(Vector3::x(), arm);
+    /// skelly.set_orientation(spine, UnitQuaternion::from_euler_angles(0.0, 0.3, 0.0));
+    /// skelly.set_orientation(arm, UnitQuaternion::from_euler_angles(0.2, 0.0, 0.5));
+    ///
+    /// let mut globals = [Isometry3::identity(); 5];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    ///
+    /// let relative = skelly.relative_isometry(hand, spine).unwrap();
+    /// let expected = globals[spine].inverse() * globals[hand];
+    /// assert!((relative.translation.vector - expected.translation.vector).magnitude() < 1e-5);
+    /// assert!(relative.rotation.angle_to(&expected.rotation) < 1e-5);
+    ///
+    /// assert!(skelly.relative_isometry(spine, hand).is_none());
+    /// assert!(skelly.relative_isometry(hand, hand).is_none());
     /// ```
     ///
     /// # Panics
     ///
     /// This method panics if `bone` index is out of bounds.
-    pub fn iter_chain(&self, mut bone: usize) -> impl Iterator<Item = usize> + '_ {
-        std::iter::from_fn(move || {
-            if let Some(parent) = self.bones[bone].parent {
-                bone = parent;
-                Some(bone)
-            } else {
-                None
+    #[track_caller]
+    pub fn relative_isometry(&self, bone: usize, ancestor: usize) -> Option<Isometry3<T>>
+    where
+        T: RealField,
+    {
+        let mut relative = self.bones[bone].isometry.clone();
+        for parent in self.iter_chain(bone) {
+            if parent == ancestor {
+                return Some(relative);
             }
-        })
+            relative = &self.bones[parent].isometry * relative;
+        }
+        None
     }
 
-    /// Iterates through the bone's direct descendants
-    /// yielding their ids.
+    /// Returns global isometry of the `bone` in current skelly transformations.
+    fn global_of(&self, bone: usize, skelly_global: &Isometry3<T>) -> Isometry3<T>
+    where
+        T: RealField,
+    {
+        skelly_global * self.cached_global(bone)
+    }
+
+    /// Returns global isometry of the `bone` in current skelly transformations
+    /// with identity skelly global isometry.
+    ///
+    /// Computed isometries of the bone and its ancestors are memoized
+    /// until any of them is modified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// assert_eq!(skelly.cached_global(tip).translation.vector, Vector3::new(2.0, 0.0, 0.0));
+    ///
+    /// skelly.set_position(bone, Vector3::y());
+    /// assert_eq!(skelly.cached_global(tip).translation.vector, Vector3::new(1.0, 1.0, 0.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn cached_global(&self, bone: usize) -> Isometry3<T>
+    where
+        T: RealField,
+    {
+        let me = &self.bones[bone];
+        me.global
+            .get_or_init(|| match me.parent {
+                Some(parent) => self.cached_global(parent) * &me.isometry,
+                None => me.isometry.clone(),
+            })
+            .clone()
+    }
+
+    /// Drops memoized global isometries of all bones.
+    fn invalidate_all_globals(&mut self) {
+        self.revision = next_revision();
+        for bone in &mut self.bones {
+            bone.global.take();
+        }
+    }
+
+    /// Drops memoized global isometries of the `bone` and its descendants.
+    ///
+    /// Memoized bone always has memoized parent,
+    /// so nothing is done if the `bone` is not memoized.\
+    /// Descendants are always placed after their ancestors.
+    fn invalidate_globals(&mut self, bone: usize) {
+        self.revision = next_revision();
+        if self.bones[bone].global.take().is_none() {
+            return;
+        }
+
+        for index in bone + 1..self.bones.len() {
+            if let Some(parent) = self.bones[index].parent {
+                // Bone with cleared parent is either a descendant or not memoized.
+                if self.bones[parent].global.get().is_none() {
+                    self.bones[index].global.take();
+                }
+            }
+        }
+    }
+
+    /// Iterates through bone ancestors up until root bone is reached
+    /// yielding their ids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// assert_eq!(skelly.iter_chain(tip).collect::<Vec<_>>(), [bone, root]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::Point3, std::panic::catch_unwind};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    ///
+    /// let panic = catch_unwind(|| skelly.iter_chain(root + 1).count()).unwrap_err();
+    /// let message = panic.downcast_ref::<String>().unwrap();
+    /// assert!(message.contains("Bone index is out of bounds"));
+    /// assert!(message.contains("index is 1, skelly has 1 bones"));
+    /// ```
+    #[track_caller]
+    pub fn iter_chain(&self, mut bone: usize) -> impl Iterator<Item = usize> + '_ {
+        assert!(
+            bone < self.bones.len(),
+            "Bone index is out of bounds: index is {}, skelly has {} bones",
+            bone,
+            self.bones.len(),
+        );
+        std::iter::from_fn(move || {
+            if let Some(parent) = self.bones[bone].parent {
+                bone = parent;
+                Some(bone)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns total length of the chain from the `bone` up to its root.
+    ///
+    /// This is the maximum distance from the root at which the `bone` can be placed
+    /// by rotating bones of the chain.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::new(5.0, 0.0, 0.0));
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::new(0.0, 2.0, 0.0), bone);
+    ///
+    /// assert_eq!(skelly.chain_length(root), 0.0);
+    /// assert_eq!(skelly.chain_length(tip), 3.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn chain_length(&self, mut bone: usize) -> T
+    where
+        T: RealField,
+    {
+        let mut length = T::zero();
+        while let Some(parent) = self.bones[bone].parent {
+            length += self.bones[bone].isometry.translation.vector.magnitude();
+            bone = parent;
+        }
+        length
+    }
+
+    /// Returns simplified copy of the skelly
+    /// with bones shorter than `min_length` merged into their parents.
+    ///
+    /// Children of merged bones are reattached to the surviving ancestor
+    /// with composed offsets, so their global isometries are preserved.
+    /// Root bones are never merged.\
+    /// Also returns mapping from bone ids of this skelly
+    /// to bone ids of the simplified one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let arm = skelly.attach(Vector3::x(), root);
+    /// let twist = skelly.attach(Vector3::x() * 0.01, arm);
+    /// let palm = skelly.attach(Vector3::x(), twist);
+    /// skelly.set_orientation(twist, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5));
+    ///
+    /// let (simple, mapping) = skelly.simplify(0.1);
+    /// assert_eq!(simple.len(), 3);
+    /// assert_eq!(mapping, [0, 1, 1, 2]);
+    ///
+    /// let mut globals = [Isometry3::identity(); 4];
+    /// skelly.write_globals(&Isometry3::identity(), &mut globals);
+    /// let mut simple_globals = [Isometry3::identity(); 3];
+    /// simple.write_globals(&Isometry3::identity(), &mut simple_globals);
+    ///
+    /// let old = globals[palm];
+    /// let new = simple_globals[mapping[palm]];
+    /// assert!((old.translation.vector - new.translation.vector).magnitude() < 1e-5);
+    /// assert!(old.rotation.angle_to(&new.rotation) < 1e-5);
+    /// ```
+    pub fn simplify(&self, min_length: T) -> (Skelly<T, D>, Vec<usize>)
+    where
+        T: RealField,
+        D: Clone,
+    {
+        let mut simple = Skelly::new();
+        let mut mapping = Vec::with_capacity(self.bones.len());
+
+        // isometry of each bone relative to its surviving counterpart.
+        let mut offsets: Vec<Isometry3<T>> = Vec::with_capacity(self.bones.len());
+
+        for bone in &self.bones {
+            match bone.parent {
+                Some(parent) => {
+                    let offset = &offsets[parent] * &bone.isometry;
+                    if bone.isometry.translation.vector.magnitude() < min_length {
+                        mapping.push(mapping[parent]);
+                        offsets.push(offset);
+                    } else {
+                        mapping.push(simple.bones.len());
+                        offsets.push(Isometry3::identity());
+                        simple.bones.push(Bone {
+                            isometry: offset,
+                            parent: Some(mapping[parent]),
+                            userdata: bone.userdata.clone(),
+                            global: OnceLock::new(),
+                        });
+                    }
+                }
+                None => {
+                    mapping.push(simple.bones.len());
+                    offsets.push(Isometry3::identity());
+                    simple.bones.push(Bone {
+                        isometry: bone.isometry.clone(),
+                        parent: None,
+                        userdata: bone.userdata.clone(),
+                        global: OnceLock::new(),
+                    });
+                }
+            }
+        }
+
+        (simple, mapping)
+    }
+
+    /// Returns number of rotational degrees of freedom
+    /// in the chain from the `tip` up to `root` inclusive.
+    ///
+    /// Chain continues up to the topmost root if `root` is `None`.\
+    /// Each bone in the chain contributes three degrees of freedom.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// assert_eq!(skelly.dof_count(tip, None), 9);
+    /// assert_eq!(skelly.dof_count(tip, Some(bone)), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `tip` index is out of bounds
+    /// or `root` is neither `tip` nor one of its ancestors.
+    #[track_caller]
+    pub fn dof_count(&self, tip: usize, root: Option<usize>) -> usize {
+        let bones = match root {
+            None => 1 + self.iter_chain(tip).count(),
+            Some(root) if root == tip => 1,
+            Some(root) => {
+                2 + self
+                    .iter_chain(tip)
+                    .position(|bone| bone == root)
+                    .expect("Root is not an ancestor of the tip")
+            }
+        };
+        bones * 3
+    }
+
+    /// Samples positions the `tip` can reach by rotating bones of the chain
+    /// from the `root` down to the `tip`'s parent.
+    ///
+    /// `out` is filled with `samples` tip positions, one per pseudo-random chain configuration.
+    /// Sequence of configurations is deterministic.\
+    /// Joints rotate freely, as joint constraints are set on solvers, not on the skelly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::new(1.0, 2.0, 3.0));
+    /// let elbow = skelly.attach(Vector3::new(2.0, 0.0, 0.0), root);
+    /// let palm = skelly.attach(Vector3::new(0.0, 1.0, 0.0), elbow);
+    ///
+    /// let mut points = Vec::new();
+    /// skelly.sample_reach(palm, root, 500, &mut points);
+    /// assert_eq!(points.len(), 500);
+    ///
+    /// let distances = points.iter().map(|point| (point - Point3::new(1.0, 2.0, 3.0)).magnitude());
+    /// assert!(distances.clone().all(|distance| distance >= 1.0 - 1e-5 && distance <= 3.0 + 1e-5));
+    ///
+    /// // Samples cover the whole annulus.
+    /// assert!(distances.clone().any(|distance| distance < 1.5));
+    /// assert!(distances.clone().any(|distance| distance > 2.5));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `tip` index is out of bounds
+    /// or `root` is neither `tip` nor one of its ancestors.
+    #[track_caller]
+    pub fn sample_reach(&self, tip: usize, root: usize, samples: usize, out: &mut Vec<Point3<T>>)
+    where
+        T: RealField + Copy,
+    {
+        let mut chain = vec![tip];
+        if root != tip {
+            let mut ancestors = self.iter_chain(tip);
+            loop {
+                let bone = ancestors
+                    .next()
+                    .expect("Root is not an ancestor of the tip");
+                chain.push(bone);
+                if bone == root {
+                    break;
+                }
+            }
+        }
+
+        let origin = self.cached_global(root).translation;
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random_rotation = || {
+            let mut uniform = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                na::convert::<f64, T>((state >> 11) as f64 / (1u64 << 53) as f64)
+            };
+            let (u1, u2, u3) = (uniform(), uniform(), uniform());
+            let (sin2, cos2) = (u2 * T::two_pi()).sin_cos();
+            let (sin3, cos3) = (u3 * T::two_pi()).sin_cos();
+            let (a, b) = ((T::one() - u1).sqrt(), u1.sqrt());
+            UnitQuaternion::new_normalize(Quaternion::new(b * cos3, a * sin2, a * cos2, b * sin3))
+        };
+
+        out.clear();
+        out.reserve(samples);
+        for _ in 0..samples {
+            let mut global = Isometry3::from_parts(origin, UnitQuaternion::identity());
+            for &bone in chain.iter().rev() {
+                let translation = self.bones[bone].isometry.translation;
+                if bone == root {
+                    global.rotation = random_rotation();
+                } else if bone == tip {
+                    global *= translation;
+                } else {
+                    global *= Isometry3::from_parts(translation, random_rotation());
+                }
+            }
+            out.push(Point3::from(global.translation.vector));
+        }
+    }
+
+    /// Iterates through the bone's direct descendants
+    /// yielding their ids.
+    ///
+    /// Children are guaranteed to be yielded in the order they were attached.\
+    /// Solvers rely on this for deterministic results.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let left = skelly.attach(Vector3::x(), root);
+    /// let right = skelly.attach(Vector3::x(), root);
+    ///
+    /// assert_eq!(skelly.iter_children(root).collect::<Vec<_>>(), [left, right]);
+    ///
+    /// // Order is kept when siblings are interleaved with other bones.
+    /// let left_palm = skelly.attach(Vector3::x(), left);
+    /// let up = skelly.attach(Vector3::z(), root);
+    /// let right_palm = skelly.attach(Vector3::x(), right);
+    /// let down = skelly.attach(-Vector3::z(), root);
+    ///
+    /// assert_eq!(skelly.iter_children(root).collect::<Vec<_>>(), [left, right, up, down]);
+    /// assert_eq!(skelly.iter_children(left).collect::<Vec<_>>(), [left_palm]);
+    /// assert_eq!(skelly.iter_children(right).collect::<Vec<_>>(), [right_palm]);
+    /// ```
+    ///
+    /// This method is not very efficient.
+    /// As it effectively scans sub-slice [bone..]
+    /// Use with caution for too complex skellies in hot-paths.
+    ///
+    /// TODO: Consider adding skelly building phase to pack siblings together.
+    /// Packing must preserve attachment order of siblings.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn iter_children(&self, parent: usize) -> impl Iterator<Item = usize> + '_ {
+        self.bones
+            .iter()
+            .enumerate()
+            .skip(parent)
+            .filter_map(move |(index, bone)| {
+                if bone.parent == Some(parent) {
+                    Some(index)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+impl<T> Skelly<T>
+where
+    T: Scalar,
+{
+    /// Creates new root bone in the skelly at specified `position`.
+    ///
+    /// Root bones are ones that have no parent bone.\
+    /// Returns id of the added root bone.\
+    ///
+    /// `skelly.add_root(pos)` is a more pleasant shorthand for `skelly.add_root_with(pos, ())`;
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::Point3};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// ```
+    pub fn add_root(&mut self, position: Point3<T>) -> usize
+    where
+        T: RealField,
+    {
+        self.add_root_with(position, ())
+    }
+
+    /// Attaches new bone to an existing bone with specified id.
+    ///
+    /// Returns id of the added bone.\
+    /// The bone will be placed `relative` to its parent.\
+    ///
+    /// `skelly.attach(relative, parent)` is a more pleasant shorthand for `skelly.attach_with(relative, parent, ())`;
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `parent` index is out of bounds.
+    #[track_caller]
+    pub fn attach(&mut self, relative: Vector3<T>, parent: usize) -> usize
+    where
+        T: RealField,
+    {
+        self.attach_with(relative, parent, ())
+    }
+
+    /// Creates new root bone in the skelly at specified `position`.
+    ///
+    /// Same as [`Skelly::add_root`] but returns typed id of the added bone.
+    pub fn add_root_typed(&mut self, position: Point3<T>) -> BoneId
+    where
+        T: RealField,
+    {
+        let index = self.add_root(position);
+        BoneId {
+            index,
+            skelly: self.tag,
+        }
+    }
+
+    /// Attaches new bone to an existing bone with specified typed id.
+    ///
+    /// Same as [`Skelly::attach`] but uses typed ids.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `parent` is id of another skelly.
+    #[track_caller]
+    pub fn attach_typed(&mut self, relative: Vector3<T>, parent: BoneId) -> BoneId
+    where
+        T: RealField,
+    {
+        let parent = self.index_of(parent);
+        let index = self.attach(relative, parent);
+        BoneId {
+            index,
+            skelly: self.tag,
+        }
+    }
+
+    /// Attaches new bone to an existing bone with specified id
+    /// placing it at `global_tip` in global space.
+    ///
+    /// `skelly.attach_at_global(tip, parent, global)` is a more pleasant shorthand
+    /// for `skelly.attach_at_global_with(tip, parent, global, ())`;
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3, UnitQuaternion}, core::f32::consts::PI};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::new(1.0, 0.0, 0.0));
+    /// skelly.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let skelly_global = Isometry3::translation(0.0, 0.0, 1.0);
+    /// let tip = skelly.attach_at_global(Point3::new(3.0, 2.0, 1.0), bone, &skelly_global);
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// skelly.write_globals(&skelly_global, &mut globals);
+    /// assert!((globals[tip].translation.vector - Vector3::new(3.0, 2.0, 1.0)).magnitude() < 1e-5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `parent` index is out of bounds.
+    #[track_caller]
+    pub fn attach_at_global(
+        &mut self,
+        global_tip: Point3<T>,
+        parent: usize,
+        skelly_global: &Isometry3<T>,
+    ) -> usize
+    where
+        T: RealField,
+    {
+        self.attach_at_global_with(global_tip, parent, skelly_global, ())
+    }
+
+    /// Attaches new bone to the most recently added bone.
+    ///
+    /// Returns id of the added bone.\
+    /// The bone will be placed `relative` to its parent.\
+    ///
+    /// `skelly.extend(relative)` is a more pleasant shorthand for `skelly.extend_with(relative, ())`;
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// let mut chain = Skelly::<f32>::new();
+    /// chain.add_root(Point3::origin());
+    /// chain.extend(Vector3::x());
+    /// chain.extend(Vector3::y());
+    /// chain.extend(Vector3::z());
+    ///
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let first = skelly.attach(Vector3::x(), root);
+    /// let second = skelly.attach(Vector3::y(), first);
+    /// skelly.attach(Vector3::z(), second);
+    ///
+    /// assert_eq!(chain, skelly);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if skelly is empty.
+    #[track_caller]
+    pub fn extend(&mut self, relative: Vector3<T>) -> usize
+    where
+        T: RealField,
+    {
+        self.extend_with(relative, ())
+    }
+}
+
+/// Read-only view of a bone in a skelly.
+///
+/// Created with [`Skelly::bone`].
+pub struct BoneView<'a, T: Scalar, D> {
+    skelly: &'a Skelly<T, D>,
+    bone: usize,
+}
+
+impl<'a, T, D> Clone for BoneView<'a, T, D>
+where
+    T: Scalar,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, D> Copy for BoneView<'a, T, D> where T: Scalar {}
+
+impl<'a, T, D> BoneView<'a, T, D>
+where
+    T: Scalar,
+{
+    /// Returns id of the bone.
+    pub fn id(&self) -> usize {
+        self.bone
+    }
+
+    /// Returns id of the bone's parent.
+    pub fn parent(&self) -> Option<usize> {
+        self.skelly.bones[self.bone].parent
+    }
+
+    /// Returns isometry of the bone relative to its parent.
+    pub fn local(&self) -> &'a Isometry3<T> {
+        &self.skelly.bones[self.bone].isometry
+    }
+
+    /// Returns userdata associated with the bone.
+    pub fn userdata(&self) -> &'a D {
+        &self.skelly.bones[self.bone].userdata
+    }
+
+    /// Returns global isometry of the bone
+    /// with specified skelly global isometry.
+    pub fn global(&self, skelly_global: &Isometry3<T>) -> Isometry3<T>
+    where
+        T: RealField,
+    {
+        self.skelly.global_of(self.bone, skelly_global)
+    }
+}
+
+/// Collection of bones transformations
+/// that represent a skelly posture.
+///
+/// It's primary usecase is to be used instead
+/// of transformations contained in the `Skelly`.
+/// Multiple postures to be processed for the same `Skelly`.
+/// Allowing running animations, IK algorithms etc,
+/// and then blend them to get final posture.
+pub struct Posture<T: Scalar> {
+    joints: Vec<Isometry3<T>>,
+    topology: u64,
+    dirty: bool,
+}
+
+impl<T> PartialEq for Posture<T>
+where
+    T: RealField,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.topology == other.topology && self.joints == other.joints
+    }
+}
+
+/// Composes postures joint-wise.
+///
+/// Adding an additive posture produced by [`Posture`] subtraction
+/// on the right of a base posture applies it to that base.
+/// For any compatible postures `(&b + &(&a - &b)) == a`.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, Isometry3, UnitQuaternion}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let bone = skelly.attach(Vector3::x(), root);
+/// skelly.set_orientation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.7));
+///
+/// let rest = Posture::new(&skelly);
+/// let mut a = Posture::new(&skelly);
+/// a.append_rotation(root, UnitQuaternion::from_euler_angles(0.3, 0.0, 0.5));
+/// a.append_rotation(bone, UnitQuaternion::from_euler_angles(0.0, 0.4, 0.0));
+/// a.set_position(bone, Vector3::new(0.0, 2.0, 1.0));
+///
+/// let additive = &a - &rest;
+/// let mut restored = &rest + &additive;
+///
+/// for bone in 0..skelly.len() {
+///     let delta = a.get_isometry(bone).inverse() * restored.get_isometry(bone);
+///     assert!(delta.translation.vector.magnitude() < 1e-5);
+///     assert!(delta.rotation.angle() < 1e-3);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if postures have different number of joints.
+impl<T> std::ops::Add for &Posture<T>
+where
+    T: RealField,
+{
+    type Output = Posture<T>;
+
+    #[track_caller]
+    fn add(self, rhs: Self) -> Posture<T> {
+        assert_eq!(
+            self.joints.len(),
+            rhs.joints.len(),
+            "Postures are not compatible"
+        );
+
+        Posture {
+            joints: self
+                .joints
+                .iter()
+                .zip(&rhs.joints)
+                .map(|(lhs, rhs)| lhs * rhs)
+                .collect(),
+            topology: self.topology,
+            dirty: true,
+        }
+    }
+}
+
+/// Decomposes postures joint-wise.
+///
+/// `&a - &b` yields additive posture that turns `b` into `a`.
+/// Each joint of the additive posture is expressed relative to the joint of `b`.
+/// See `Add` implementation for [`Posture`].
+///
+/// # Panics
+///
+/// Panics if postures have different number of joints.
+impl<T> std::ops::Sub for &Posture<T>
+where
+    T: RealField,
+{
+    type Output = Posture<T>;
+
+    #[track_caller]
+    fn sub(self, rhs: Self) -> Posture<T> {
+        assert_eq!(
+            self.joints.len(),
+            rhs.joints.len(),
+            "Postures are not compatible"
+        );
+
+        Posture {
+            joints: self
+                .joints
+                .iter()
+                .zip(&rhs.joints)
+                .map(|(lhs, rhs)| rhs.inverse() * lhs)
+                .collect(),
+            topology: self.topology,
+            dirty: true,
+        }
+    }
+}
+
+impl<T> Posture<T>
+where
+    T: Scalar,
+{
+    /// Returns new `Posture` instance for `skelly`.
+    /// Copies current `skelly` transformations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// ```
+    pub fn new<D>(skelly: &Skelly<T, D>) -> Self
+    where
+        T: RealField,
+    {
+        Posture {
+            joints: skelly
+                .bones
+                .iter()
+                .map(|bone| bone.isometry.clone())
+                .collect(),
+            topology: skelly.topology_hash(),
+            dirty: false,
+        }
+    }
+
+    /// Resets this posture to current `skelly` transformations.
+    ///
+    /// Same as [`Posture::new`] but reuses allocated memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// assert!(!posture.is_dirty());
+    ///
+    /// posture.append_rotation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5));
+    /// assert!(posture.is_dirty());
+    ///
+    /// posture.reset_to(&skelly);
+    /// assert!(!posture.is_dirty());
+    /// ```
+    pub fn reset_to<D>(&mut self, skelly: &Skelly<T, D>)
+    where
+        T: RealField,
+    {
+        self.joints.clear();
+        self.joints
+            .extend(skelly.bones.iter().map(|bone| bone.isometry.clone()));
+        self.topology = skelly.topology_hash();
+        self.dirty = false;
+    }
+
+    /// Copies joints of the `other` posture into this one.
+    #[cfg(feature = "ik")]
+    pub(crate) fn copy_from(&mut self, other: &Posture<T>) {
+        self.joints.clone_from(&other.joints);
+        self.topology = other.topology;
+        self.dirty = true;
+    }
+
+    /// Replaces joints with NaN or infinite components
+    /// with rest transformations of the `skelly`.
+    ///
+    /// Returns number of replaced joints.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let bend = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5);
+    /// let mut posture = Posture::new(&skelly);
+    /// posture.set_orientation(root, bend);
+    /// posture.set_position(bone, Vector3::new(f32::NAN, 0.0, 0.0));
+    ///
+    /// assert_eq!(posture.sanitize(&skelly), 1);
+    /// assert_eq!(posture.get_isometry(bone), skelly.get_isometry(bone));
+    /// assert_eq!(*posture.get_orientation(root), bend);
+    ///
+    /// assert_eq!(posture.sanitize(&skelly), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `posture` is not compatible with the `skelly`.
+    #[track_caller]
+    pub fn sanitize<D>(&mut self, skelly: &Skelly<T, D>) -> usize
+    where
+        T: RealField,
+    {
+        assert!(
+            self.is_compatible(skelly),
+            "Posture is not compatible with the skelly"
+        );
+
+        let mut count = 0;
+        for (joint, bone) in self.joints.iter_mut().zip(&skelly.bones) {
+            let finite = joint.translation.vector.iter().all(|c| c.is_finite())
+                && joint.rotation.coords.iter().all(|c| c.is_finite());
+
+            if !finite {
+                *joint = bone.isometry.clone();
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    /// Returns `true` if this posture was modified
+    /// since it was created or reset to a skelly.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Returns new `Posture` instance for `skelly`
+    /// with identity transformation for every joint.
+    ///
+    /// Unlike [`Posture::new`] this ignores `skelly` transformations.
+    /// Useful as neutral base for additive postures.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, Isometry3}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::new(1.0, 2.0, 3.0));
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut posture = Posture::identity(&skelly);
+    /// for bone in 0..skelly.len() {
+    ///     assert_eq!(*posture.get_isometry(bone), Isometry3::identity());
+    /// }
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// posture.write_globals(&skelly, &Isometry3::translation(1.0, 0.0, 0.0), &mut globals);
+    /// assert!(globals.iter().all(|global| *global == globals[root]));
+    /// ```
+    pub fn identity<D>(skelly: &Skelly<T, D>) -> Self
+    where
+        T: RealField,
+    {
+        Posture {
+            joints: vec![Isometry3::identity(); skelly.bones.len()],
+            topology: skelly.topology_hash(),
+            dirty: false,
+        }
+    }
+
+    /// Removes all joints from the posture.
+    ///
+    /// Allocated memory is kept for reuse.\
+    /// Cleared posture is compatible with empty skellies.
+    pub fn clear(&mut self) {
+        self.joints.clear();
+        self.topology = TOPOLOGY_HASH_BASIS;
+        self.dirty = false;
+    }
+
+    /// Checks if this posture can be used with the `skelly`.
+    ///
+    /// Posture is compatible with skellies that have the same hierarchy
+    /// as one used to create the posture.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3}};
+    /// let mut chain = Skelly::<f32>::new();
+    /// let root = chain.add_root(Point3::origin());
+    /// let bone = chain.attach(Vector3::x(), root);
+    /// chain.attach(Vector3::x(), bone);
+    ///
+    /// let mut fork = Skelly::<f32>::new();
+    /// let root = fork.add_root(Point3::origin());
+    /// fork.attach(Vector3::x(), root);
+    /// fork.attach(-Vector3::x(), root);
+    ///
+    /// let posture = Posture::new(&chain);
+    /// assert_eq!(chain.len(), fork.len());
+    /// assert!(posture.is_compatible(&chain));
+    /// assert!(!posture.is_compatible(&fork));
+    /// ```
+    pub fn is_compatible<D>(&self, skelly: &Skelly<T, D>) -> bool {
+        self.joints.len() == skelly.bones.len() && self.topology == skelly.topology_hash()
+    }
+
+    /// Rotates bone with specified id.
+    ///
+    /// *Does not* affect relative position to the parent and global position for root bones.
+    /// Affects global position of all descendant bones.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::Skelly, na::{Point3, Vector3, Isometry3}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
-    /// let left = skelly.attach(Vector3::x(), root);
-    /// let right = skelly.attach(Vector3::x(), root);
+    /// let bone = skelly.attach(Vector3::x(), root);
     ///
-    /// assert_eq!(skelly.iter_children(root).collect::<Vec<_>>(), [left, right]);
-    /// ```
+    /// let mut posture = Posture::new(&skelly);
     ///
-    /// This method is not very efficient.
-    /// As it effectively scans sub-slice [bone..]
-    /// Use with caution for too complex skellies in hot-paths.
+    /// let mut globals = [Isometry3::identity(); 2];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_old = globals[bone];
     ///
-    /// TODO: Consider adding skelly building phase to pack siblings together.
+    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
+    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    ///
+    /// // Rotate root bone. It is still at origin.
+    /// // Yet global position of the `bone` attached to `root` has changed accordingly.
+    /// posture.append_rotation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    ///
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_new = globals[bone];
+    ///
+    /// // Ensure that bone is placed correctly in global space after root rotation at (0, 1, 0).
+    /// assert!((bone_global_new.translation.vector - Vector3::y()).magnitude() < EPSILON);
+    /// ```
     ///
     /// # Panics
     ///
     /// This method panics if `bone` index is out of bounds.
     #[track_caller]
-    pub fn iter_children(&self, parent: usize) -> impl Iterator<Item = usize> + '_ {
-        self.bones
-            .iter()
-            .enumerate()
-            .skip(parent)
-            .filter_map(move |(index, bone)| {
-                if bone.parent == Some(parent) {
-                    Some(index)
-                } else {
-                    None
-                }
-            })
+    pub fn append_rotation(&mut self, bone: usize, rotation: UnitQuaternion<T>)
+    where
+        T: RealField,
+    {
+        self.joints[bone].rotation *= rotation;
+        self.dirty = true;
     }
-}
 
-impl<T> Skelly<T>
-where
-    T: Scalar,
-{
-    /// Creates new root bone in the skelly at specified `position`.
-    ///
-    /// Root bones are ones that have no parent bone.\
-    /// Returns id of the added root bone.\
+    /// Rotates each of specified `bones` by the same `rotation`.
     ///
-    /// `skelly.add_root(pos)` is a more pleasant shorthand for `skelly.add_root_with(pos, ())`;
+    /// Same as calling [`Posture::append_rotation`] for each bone.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::Skelly, na::Point3};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, UnitQuaternion, Vector3}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
+    /// let bones = [
+    ///     skelly.attach(Vector3::x(), root),
+    ///     skelly.attach(Vector3::y(), root),
+    ///     skelly.attach(Vector3::z(), root),
+    /// ];
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// let rotation = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+    /// posture.append_rotation_many(&bones, rotation);
+    ///
+    /// for bone in bones {
+    ///     assert_eq!(*posture.get_orientation(bone), rotation);
+    /// }
+    /// assert_eq!(*posture.get_orientation(root), UnitQuaternion::identity());
     /// ```
-    pub fn add_root(&mut self, position: Point3<T>) -> usize
+    ///
+    /// # Panics
+    ///
+    /// This method panics if any of `bones` indices is out of bounds.
+    #[track_caller]
+    pub fn append_rotation_many(&mut self, bones: &[usize], rotation: UnitQuaternion<T>)
     where
         T: RealField,
     {
-        self.add_root_with(position, ())
+        for &bone in bones {
+            self.joints[bone].rotation *= &rotation;
+        }
+        self.dirty = true;
     }
 
-    /// Attaches new bone to an existing bone with specified id.
-    ///
-    /// Returns id of the added bone.\
-    /// The bone will be placed `relative` to its parent.\
+    /// Rotates bone with specified id.
     ///
-    /// `skelly.attach(relative, parent)` is a more pleasant shorthand for `skelly.attach_with(relative, parent, ())`;
+    /// *Does not* affect relative position to the parent and global position for root bones.
+    /// Affects global position of all descendant bones.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::Skelly, na::{Point3, Vector3}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    ///
+    /// let mut globals = [Isometry3::identity(); 2];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_old = globals[bone];
+    ///
+    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
+    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    ///
+    /// // Rotate the bone. It is still at origin.
+    /// // Yet global position of the `bone` attached to `root` has changed accordingly.
+    /// posture.prepend_rotation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    ///
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_new = globals[bone];
+    ///
+    /// // Ensure that bone is placed correctly in global space after root rotation at (0, 1, 0).
+    /// assert!((bone_global_new.translation.vector - Vector3::y()).magnitude() < EPSILON);
     /// ```
     ///
     /// # Panics
     ///
-    /// This method panics if `parent` index is out of bounds.
+    /// This method panics if `bone` index is out of bounds.
     #[track_caller]
-    pub fn attach(&mut self, relative: Vector3<T>, parent: usize) -> usize
+    pub fn prepend_rotation(&mut self, bone: usize, rotation: UnitQuaternion<T>)
     where
         T: RealField,
     {
-        self.attach_with(relative, parent, ())
+        let my_isometry = &mut self.joints[bone];
+        *my_isometry = rotation * &*my_isometry;
+        self.dirty = true;
     }
-}
-
-/// Collection of bones transformations
-/// that represent a skelly posture.
-///
-/// It's primary usecase is to be used instead
-/// of transformations contained in the `Skelly`.
-/// Multiple postures to be processed for the same `Skelly`.
-/// Allowing running animations, IK algorithms etc,
-/// and then blend them to get final posture.
-pub struct Posture<T: Scalar> {
-    joints: Vec<Isometry3<T>>,
-}
 
-impl<T> Posture<T>
-where
-    T: Scalar,
-{
-    /// Returns new `Posture` instance for `skelly`.
-    /// Copies current `skelly` transformations.
+    /// Translates bone with specified id.
+    ///
+    /// Affects relative position to the parent and global position for root bones.
+    /// Affects global position of all descendant bones.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
     ///
     /// let mut posture = Posture::new(&skelly);
+    ///
+    /// let mut globals = [Isometry3::identity(); 2];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_old = globals[bone];
+    ///
+    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
+    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    ///
+    /// // Translate root bone.
+    /// // Global position of the `bone` attached to `root` has changed accordingly.
+    /// posture.append_translation(root, Vector3::z().into());
+    ///
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let bone_global_new = globals[bone];
+    ///
+    /// // Ensure that bone is placed correctly in global space after root translation at (1, 0, 1).
+    /// assert!((bone_global_new.translation.vector - (Vector3::x() + Vector3::z())).magnitude() < EPSILON);
     /// ```
-    pub fn new<D>(skelly: &Skelly<T, D>) -> Self
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn append_translation(&mut self, bone: usize, translation: Translation3<T>)
     where
         T: RealField,
     {
-        Posture {
-            joints: skelly
-                .bones
-                .iter()
-                .map(|bone| bone.isometry.clone())
-                .collect(),
-        }
-    }
-
-    pub fn is_compatible<D>(&self, skelly: &Skelly<T, D>) -> bool {
-        self.joints.len() == skelly.bones.len()
+        self.joints[bone].translation *= translation;
+        self.dirty = true;
     }
 
-    /// Rotates bone with specified id.
-    ///
-    /// *Does not* affect relative position to the parent and global position for root bones.
+    /// Sets relative position for bone with specified id.
     /// Affects global position of all descendant bones.
     ///
+    /// This method ignores current relative position of the bone.
+    /// To apply translation to current relative poistion see [`Skelly::append_translation`].
+    ///
     /// # Example
     ///
     /// ```
@@ -700,33 +2837,40 @@ where
     /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
     /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
     ///
-    /// // Rotate root bone. It is still at origin.
-    /// // Yet global position of the `bone` attached to `root` has changed accordingly.
-    /// posture.append_rotation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    /// // Set new relative position for the `bone`.
+    /// posture.set_position(bone, Vector3::z());
     ///
     /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
     /// let bone_global_new = globals[bone];
     ///
-    /// // Ensure that bone is placed correctly in global space after root rotation at (0, 1, 0).
-    /// assert!((bone_global_new.translation.vector - Vector3::y()).magnitude() < EPSILON);
+    /// // Ensure that bone is placed correctly in global space at new position (0, 0, 1).
+    /// assert!((bone_global_new.translation.vector - Vector3::z()).magnitude() < EPSILON);
     /// ```
     ///
     /// # Panics
     ///
     /// This method panics if `bone` index is out of bounds.
     #[track_caller]
-    pub fn append_rotation(&mut self, bone: usize, rotation: UnitQuaternion<T>)
+    pub fn set_position(&mut self, bone: usize, position: Vector3<T>) {
+        self.joints[bone].translation = position.into();
+        self.dirty = true;
+    }
+
+    /// Returns current bone position relative to parent.
+    #[track_caller]
+    pub fn get_position(&mut self, bone: usize) -> &Vector3<T>
     where
         T: RealField,
     {
-        self.joints[bone].rotation *= rotation
+        &self.joints[bone].translation.vector
     }
 
-    /// Rotates bone with specified id.
-    ///
-    /// *Does not* affect relative position to the parent and global position for root bones.
+    /// Sets relative orientation for bone with specified id.
     /// Affects global position of all descendant bones.
     ///
+    /// This method ignores current relative position of the bone.
+    /// To apply translation to current relative poistion see [`Skelly::append_translation`].
+    ///
     /// # Example
     ///
     /// ```
@@ -744,14 +2888,13 @@ where
     /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
     /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
     ///
-    /// // Rotate the bone. It is still at origin.
-    /// // Yet global position of the `bone` attached to `root` has changed accordingly.
-    /// posture.prepend_rotation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    /// // Set new relative orientation for the `bone`.
+    /// posture.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
     ///
     /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
     /// let bone_global_new = globals[bone];
     ///
-    /// // Ensure that bone is placed correctly in global space after root rotation at (0, 1, 0).
+    /// // Ensure that bone is placed correctly in global space at new position (0, 0, 1).
     /// assert!((bone_global_new.translation.vector - Vector3::y()).magnitude() < EPSILON);
     /// ```
     ///
@@ -759,165 +2902,391 @@ where
     ///
     /// This method panics if `bone` index is out of bounds.
     #[track_caller]
-    pub fn prepend_rotation(&mut self, bone: usize, rotation: UnitQuaternion<T>)
+    pub fn set_orientation(&mut self, bone: usize, orientation: UnitQuaternion<T>) {
+        self.joints[bone].rotation = orientation;
+        self.dirty = true;
+    }
+
+    /// Returns current bone orientation relative to parent.
+    #[track_caller]
+    pub fn get_orientation(&mut self, bone: usize) -> &UnitQuaternion<T>
     where
         T: RealField,
     {
-        let my_isometry = &mut self.joints[bone];
-        *my_isometry = rotation * &*my_isometry;
+        &self.joints[bone].rotation
     }
 
-    /// Translates bone with specified id.
+    /// Returns current bone isometry relative to parent.
+    #[track_caller]
+    pub fn get_isometry(&mut self, bone: usize) -> &Isometry3<T>
+    where
+        T: RealField,
+    {
+        &self.joints[bone]
+    }
+
+    /// Rotates bone with specified id by `rotation` in global space.
+    ///
+    /// Rotation is converted into the parent's frame
+    /// and composed with current relative orientation of the bone.
+    /// Affects global position of all descendant bones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::consts::PI};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// posture.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    ///
+    /// let first = UnitQuaternion::from_euler_angles(PI / 4.0, 0.0, 0.0);
+    /// let second = UnitQuaternion::from_euler_angles(0.0, PI / 3.0, 0.0);
+    ///
+    /// let mut globals = [Isometry3::identity(); 3];
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// let initial = globals[bone].rotation;
+    ///
+    /// posture.rotate_global(bone, &skelly, first);
+    /// posture.rotate_global(bone, &skelly, second);
+    ///
+    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    /// assert!(globals[bone].rotation.angle_to(&(second * first * initial)) < 1e-5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bone` index is out of bounds.
+    #[track_caller]
+    pub fn rotate_global<D>(
+        &mut self,
+        bone: usize,
+        skelly: &Skelly<T, D>,
+        rotation: UnitQuaternion<T>,
+    ) where
+        T: RealField,
+    {
+        let parent = self.parent_global(bone, skelly).rotation;
+        let joint = &mut self.joints[bone];
+        joint.rotation = parent.inverse() * rotation * parent * &joint.rotation;
+        self.dirty = true;
+    }
+
+    /// Moves the `bone` by `delta` in global space.
     ///
-    /// Affects relative position to the parent and global position for root bones.
-    /// Affects global position of all descendant bones.
+    /// Delta is converted into the parent's frame
+    /// and applied to the bone's relative position.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::consts::PI};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
     ///
     /// let mut posture = Posture::new(&skelly);
+    /// posture.set_orientation(root, UnitQuaternion::from_euler_angles(0.3, 0.0, PI / 2.0));
+    /// posture.set_orientation(bone, UnitQuaternion::from_euler_angles(0.0, 0.7, 0.0));
     ///
-    /// let mut globals = [Isometry3::identity(); 2];
+    /// let mut globals = [Isometry3::identity(); 3];
     /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_old = globals[bone];
-    ///
-    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
-    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    /// let initial = globals[tip].translation.vector;
     ///
-    /// // Translate root bone.
-    /// // Global position of the `bone` attached to `root` has changed accordingly.
-    /// posture.append_translation(root, Vector3::z().into());
+    /// posture.translate_global(tip, &skelly, Vector3::y());
     ///
     /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_new = globals[bone];
-    ///
-    /// // Ensure that bone is placed correctly in global space after root translation at (1, 0, 1).
-    /// assert!((bone_global_new.translation.vector - (Vector3::x() + Vector3::z())).magnitude() < EPSILON);
+    /// assert!((globals[tip].translation.vector - initial - Vector3::y()).magnitude() < 1e-5);
     /// ```
     ///
     /// # Panics
     ///
     /// This method panics if `bone` index is out of bounds.
     #[track_caller]
-    pub fn append_translation(&mut self, bone: usize, translation: Translation3<T>)
+    pub fn translate_global<D>(&mut self, bone: usize, skelly: &Skelly<T, D>, delta: Vector3<T>)
     where
         T: RealField,
     {
-        self.joints[bone].translation *= translation;
+        let parent = self.parent_global(bone, skelly).rotation;
+        self.joints[bone].translation.vector += parent.inverse_transform_vector(&delta);
+        self.dirty = true;
     }
 
-    /// Sets relative position for bone with specified id.
-    /// Affects global position of all descendant bones.
+    /// Blends this posture toward `other` in global space.
     ///
-    /// This method ignores current relative position of the bone.
-    /// To apply translation to current relative poistion see [`Skelly::append_translation`].
+    /// Global isometries of each bone are interpolated by `t`
+    /// and converted back to joint isometries.\
+    /// Unlike blending joints, this avoids foldover of bones
+    /// when postures differ a lot.
+    ///
+    /// `t` is clamped to `[0, 1]` range, so postures are never extrapolated.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, Isometry3, UnitQuaternion}, core::f32::consts::PI};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
     ///
-    /// let mut posture = Posture::new(&skelly);
+    /// let curl = UnitQuaternion::from_euler_angles(0.0, 0.0, PI * 0.9);
+    /// let mut curled = Posture::new(&skelly);
+    /// curled.set_orientation(bone, curl);
+    /// let straight = Posture::new(&skelly);
+    ///
+    /// let tip_of = |posture: &Posture<f32>| {
+    ///     let mut globals = [Isometry3::identity(); 3];
+    ///     posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
+    ///     globals[tip].translation.vector
+    /// };
+    /// let midpoint = (tip_of(&curled) + tip_of(&straight)) * 0.5;
+    ///
+    /// // Blending joints swings the tip around.
+    /// let mut local = Posture::new(&skelly);
+    /// local.set_orientation(bone, curl.slerp(&UnitQuaternion::identity(), 0.5));
+    /// assert!((tip_of(&local) - midpoint).magnitude() > 0.5);
+    ///
+    /// let mut global = curled;
+    /// global.blend_global(&skelly, &straight, 0.5);
+    /// assert!((tip_of(&global) - midpoint).magnitude() < 1e-5);
+    ///
+    /// // Factor beyond one blends to the end.
+    /// let blended = |t| {
+    ///     let mut posture = Posture::new(&skelly);
+    ///     posture.set_orientation(bone, curl);
+    ///     posture.blend_global(&skelly, &straight, t);
+    ///     posture
+    /// };
+    /// let (mut end, mut overshoot) = (blended(1.0), blended(1.5));
+    /// for joint in [root, bone, tip] {
+    ///     assert_eq!(overshoot.get_isometry(joint), end.get_isometry(joint));
+    /// }
+    /// ```
     ///
-    /// let mut globals = [Isometry3::identity(); 2];
-    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_old = globals[bone];
+    /// # Panics
     ///
-    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
-    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    /// Panics if either posture is not compatible with the `skelly`.
+    #[track_caller]
+    pub fn blend_global<D>(&mut self, skelly: &Skelly<T, D>, other: &Posture<T>, t: T)
+    where
+        T: RealField + Copy,
+    {
+        assert!(
+            self.is_compatible(skelly) && other.is_compatible(skelly),
+            "Posture is not compatible with the skelly"
+        );
+
+        let t = t.max(T::zero()).min(T::one());
+
+        let mut globals = vec![Isometry3::identity(); skelly.len()];
+        let mut other_globals = vec![Isometry3::identity(); skelly.len()];
+        self.write_globals(skelly, &Isometry3::identity(), &mut globals);
+        other.write_globals(skelly, &Isometry3::identity(), &mut other_globals);
+
+        for (global, other) in globals.iter_mut().zip(&other_globals) {
+            *global = global.lerp_slerp(other, t);
+        }
+
+        for (index, bone) in skelly.bones.iter().enumerate() {
+            self.joints[index] = match bone.parent {
+                Some(parent) => globals[parent].inv_mul(&globals[index]),
+                None => globals[index],
+            };
+        }
+        self.dirty = true;
+    }
+
+    /// Rotates each joint toward the `target` posture by at most `max_angle`.
     ///
-    /// // Set new relative position for the `bone`.
-    /// posture.set_position(bone, Vector3::z());
+    /// Joint translation is interpolated by the same fraction as rotation.\
+    /// Joints within `max_angle` from the target, including joints
+    /// that differ only in translation, assume target isometry.
     ///
-    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_new = globals[bone];
+    /// # Example
     ///
-    /// // Ensure that bone is placed correctly in global space at new position (0, 0, 1).
-    /// assert!((bone_global_new.translation.vector - Vector3::z()).magnitude() < EPSILON);
+    /// ```
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, UnitQuaternion}};
+    /// let mut skelly = Skelly::<f32>::new();
+    /// let root = skelly.add_root(Point3::origin());
+    /// let bone = skelly.attach(Vector3::x(), root);
+    ///
+    /// let mut target = Posture::new(&skelly);
+    /// target.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, 2.0));
+    /// target.set_orientation(bone, UnitQuaternion::from_euler_angles(1.5, 0.5, 0.0));
+    ///
+    /// let mut posture = Posture::new(&skelly);
+    /// posture.slerp_toward(&target, 0.1);
+    ///
+    /// for joint in [root, bone] {
+    ///     let rest = skelly.get_orientation(joint);
+    ///     assert!((rest.angle_to(posture.get_orientation(joint)) - 0.1).abs() < 1e-5);
+    /// }
     /// ```
     ///
     /// # Panics
     ///
-    /// This method panics if `bone` index is out of bounds.
-    #[track_caller]
-    pub fn set_position(&mut self, bone: usize, position: Vector3<T>) {
-        self.joints[bone].translation = position.into();
-    }
-
-    /// Returns current bone position relative to parent.
+    /// Panics if postures are not created for the same skelly hierarchy.
     #[track_caller]
-    pub fn get_position(&mut self, bone: usize) -> &Vector3<T>
+    pub fn slerp_toward(&mut self, target: &Posture<T>, max_angle: T)
     where
-        T: RealField,
+        T: RealField + Copy,
     {
-        &self.joints[bone].translation.vector
+        assert!(
+            self.topology == target.topology && self.joints.len() == target.joints.len(),
+            "Postures are not compatible"
+        );
+
+        for (joint, target) in self.joints.iter_mut().zip(&target.joints) {
+            let angle = joint.rotation.angle_to(&target.rotation);
+            if angle <= max_angle {
+                *joint = *target;
+            } else {
+                let t = max_angle / angle;
+                joint.rotation = joint.rotation.slerp(&target.rotation, t);
+                joint.translation.vector =
+                    joint.translation.vector.lerp(&target.translation.vector, t);
+            }
+        }
+        self.dirty = true;
     }
 
-    /// Sets relative orientation for bone with specified id.
-    /// Affects global position of all descendant bones.
+    /// Snaps rotations of joints that differ from rest rotation
+    /// by less than `angle_threshold` back to rest.
     ///
-    /// This method ignores current relative position of the bone.
-    /// To apply translation to current relative poistion see [`Skelly::append_translation`].
+    /// Removes tiny noise rotations left by solvers.\
+    /// Translations are left intact.
     ///
     /// # Example
     ///
     /// ```
-    /// # use {skelly::{Skelly, Posture}, na::{Point3, Isometry3, UnitQuaternion, Vector3}, core::f32::{consts::PI, EPSILON}};
+    /// # use {skelly::{Skelly, Posture}, na::{Point3, Vector3, UnitQuaternion}};
     /// let mut skelly = Skelly::<f32>::new();
     /// let root = skelly.add_root(Point3::origin());
     /// let bone = skelly.attach(Vector3::x(), root);
+    /// let tip = skelly.attach(Vector3::x(), bone);
+    /// skelly.set_orientation(bone, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.7));
     ///
-    /// let mut posture = Posture::new(&skelly);
-    ///
-    /// let mut globals = [Isometry3::identity(); 2];
-    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_old = globals[bone];
-    ///
-    /// // Ensure that bone is placed correctly in global space at (1, 0, 0).
-    /// assert!((bone_global_old.translation.vector - Vector3::x()).magnitude() < EPSILON);
+    /// let noise = UnitQuaternion::from_euler_angles(0.001, -0.002, 0.001);
+    /// let bend = UnitQuaternion::from_euler_angles(0.0, 0.3, 0.0);
     ///
-    /// // Set new relative orientation for the `bone`.
-    /// posture.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0));
+    /// let mut posture = Posture::new(&skelly);
+    /// posture.append_rotation(root, noise);
+    /// posture.append_rotation(bone, noise);
+    /// posture.append_rotation(tip, bend);
     ///
-    /// posture.write_globals(&skelly, &Isometry3::identity(), &mut globals);
-    /// let bone_global_new = globals[bone];
+    /// posture.denoise(&skelly, 0.01);
     ///
-    /// // Ensure that bone is placed correctly in global space at new position (0, 0, 1).
-    /// assert!((bone_global_new.translation.vector - Vector3::y()).magnitude() < EPSILON);
+    /// assert_eq!(posture.get_orientation(root), skelly.get_orientation(root));
+    /// assert_eq!(posture.get_orientation(bone), skelly.get_orientation(bone));
+    /// assert_eq!(*posture.get_orientation(tip), bend);
     /// ```
     ///
     /// # Panics
     ///
-    /// This method panics if `bone` index is out of bounds.
+    /// Panics if `posture` is not compatible with the `skelly`.
     #[track_caller]
-    pub fn set_orientation(&mut self, bone: usize, orientation: UnitQuaternion<T>) {
-        self.joints[bone].rotation = orientation;
+    pub fn denoise<D>(&mut self, skelly: &Skelly<T, D>, angle_threshold: T)
+    where
+        T: RealField + Copy,
+    {
+        assert!(
+            self.is_compatible(skelly),
+            "Posture is not compatible with the skelly"
+        );
+
+        for (joint, bone) in self.joints.iter_mut().zip(&skelly.bones) {
+            let rest = &bone.isometry.rotation;
+            if joint.rotation != *rest && joint.rotation.angle_to(rest) < angle_threshold {
+                joint.rotation = *rest;
+                self.dirty = true;
+            }
+        }
     }
 
-    /// Returns current bone orientation relative to parent.
-    #[track_caller]
-    pub fn get_orientation(&mut self, bone: usize) -> &UnitQuaternion<T>
+    /// Returns global isometry of the `bone`'s parent in this posture.
+    /// Returns identity for root bones.
+    pub(crate) fn parent_global<D>(&self, bone: usize, skelly: &Skelly<T, D>) -> Isometry3<T>
     where
         T: RealField,
     {
-        &self.joints[bone].rotation
+        skelly
+            .iter_chain(bone)
+            .fold(Isometry3::identity(), |global, ancestor| {
+                &self.joints[ancestor] * global
+            })
     }
 
-    /// Returns current bone isometry relative to parent.
+    /// Fills slice of `Matrix4` with skinning matrices
+    /// for each bone of the `skelly` in this posture.
+    ///
+    /// Skinning matrix is bone's global isometry multiplied by its inverse bind isometry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this posture is not compatible with the `skelly`
+    /// or `inverse_binds` length does not match number of bones.
     #[track_caller]
-    pub fn get_isometry(&mut self, bone: usize) -> &Isometry3<T>
+    pub fn skinning_matrices<D>(
+        &self,
+        skelly: &Skelly<T, D>,
+        skelly_global: &Isometry3<T>,
+        inverse_binds: &[Isometry3<T>],
+        out: &mut [Matrix4<T>],
+    ) where
+        T: RealField,
+    {
+        assert_eq!(
+            self.joints.len(),
+            skelly.len(),
+            "Posture is not compatible with the skelly"
+        );
+        assert_eq!(
+            inverse_binds.len(),
+            skelly.len(),
+            "Inverse binds are not compatible with the skelly"
+        );
+
+        write_skinning_matrices(
+            self.joints
+                .iter()
+                .zip(&skelly.bones)
+                .map(|(isometry, bone)| (isometry, bone.parent)),
+            skelly_global,
+            inverse_binds,
+            out,
+        )
+    }
+
+    /// Transforms all root bones of the `skelly` in this posture
+    /// by specified isometry, moving the whole skelly.
+    ///
+    /// See [`Skelly::transform_roots`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this posture is not compatible with the `skelly`.
+    #[track_caller]
+    pub fn transform_roots<D>(&mut self, skelly: &Skelly<T, D>, by: &Isometry3<T>)
     where
         T: RealField,
     {
-        &self.joints[bone]
+        assert_eq!(
+            self.joints.len(),
+            skelly.len(),
+            "Posture is not compatible with the skelly"
+        );
+
+        for (isometry, bone) in self.joints.iter_mut().zip(&skelly.bones) {
+            if bone.parent.is_none() {
+                *isometry = by * &*isometry;
+            }
+        }
+        self.dirty = true;
     }
 
     /// Fills slice of `Isometry3` with global isometries
@@ -946,6 +3315,9 @@ where
     /// One may use [`Posture`] with [`Skelly`] used to create that [`Posture`]
     /// (see [`Posture::new`]) as it is guaranteed to be compatible
     /// until new bone is added.
+    ///
+    /// Like [`Skelly::write_globals`], in debug builds panics
+    /// if a bone of the `skelly` is placed before its parent.
     pub fn write_globals<D>(
         &self,
         skelly: &Skelly<T, D>,
@@ -967,7 +3339,12 @@ where
             .enumerate()
             .for_each(|(index, (isometry, bone))| match bone.parent {
                 Some(parent) => {
-                    debug_assert!(parent < index);
+                    debug_assert!(
+                        parent < index,
+                        "Bone {} is placed before its parent {}. Global isometry of the parent is not computed yet",
+                        index,
+                        parent,
+                    );
                     globals[index] = &globals[parent] * isometry;
                 }
                 None => {
@@ -976,3 +3353,262 @@ where
             })
     }
 }
+
+/// Interpolates between two consecutive postures.
+///
+/// Useful to render postures received at discrete ticks
+/// at arbitrary moments between them.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, PostureInterpolator}, na::{Point3, Vector3, UnitQuaternion}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let bone = skelly.attach(Vector3::x(), root);
+///
+/// let first = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.2);
+/// let second = UnitQuaternion::from_euler_angles(0.0, 0.0, 1.0);
+///
+/// let mut from = Posture::new(&skelly);
+/// from.set_orientation(bone, first);
+/// let mut to = Posture::new(&skelly);
+/// to.set_orientation(bone, second);
+/// to.set_position(root, Vector3::new(4.0, 0.0, 0.0));
+///
+/// let interpolator = PostureInterpolator::new(from, to);
+/// let mut out = Posture::new(&skelly);
+/// interpolator.sample(0.25, &mut out);
+///
+/// assert!(out.get_orientation(bone).angle_to(&first.slerp(&second, 0.25)) < 1e-5);
+/// assert!((out.get_position(root) - Vector3::new(1.0, 0.0, 0.0)).magnitude() < 1e-5);
+/// ```
+pub struct PostureInterpolator<T: Scalar> {
+    from: Posture<T>,
+    to: Posture<T>,
+}
+
+impl<T> PostureInterpolator<T>
+where
+    T: Scalar,
+{
+    /// Returns new interpolator between `from` and `to` postures.
+    ///
+    /// # Panics
+    ///
+    /// Panics if postures are not created for the same skelly hierarchy.
+    #[track_caller]
+    pub fn new(from: Posture<T>, to: Posture<T>) -> Self {
+        assert!(
+            from.topology == to.topology && from.joints.len() == to.joints.len(),
+            "Postures are not compatible"
+        );
+        PostureInterpolator { from, to }
+    }
+
+    /// Makes current end posture the start one
+    /// and copies `posture` as the new end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `posture` is not created for the same skelly hierarchy.
+    #[track_caller]
+    pub fn push(&mut self, posture: &Posture<T>) {
+        assert!(
+            posture.topology == self.to.topology && posture.joints.len() == self.to.joints.len(),
+            "Postures are not compatible"
+        );
+        std::mem::swap(&mut self.from, &mut self.to);
+        self.to.joints.clone_from(&posture.joints);
+        self.to.dirty = posture.dirty;
+    }
+
+    /// Writes posture interpolated by `t` between start and end postures into `out`.
+    ///
+    /// Rotations are interpolated along the shortest arc.
+    /// `t` is clamped to `[0, 1]` range, so postures are never extrapolated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not created for the same skelly hierarchy.
+    #[track_caller]
+    pub fn sample(&self, t: T, out: &mut Posture<T>)
+    where
+        T: RealField + Copy,
+    {
+        assert!(
+            out.topology == self.from.topology && out.joints.len() == self.from.joints.len(),
+            "Postures are not compatible"
+        );
+
+        let t = t.max(T::zero()).min(T::one());
+
+        for ((out, from), to) in out
+            .joints
+            .iter_mut()
+            .zip(&self.from.joints)
+            .zip(&self.to.joints)
+        {
+            let mut to_rotation = to.rotation;
+            if from.rotation.coords.dot(&to_rotation.coords) < T::zero() {
+                to_rotation = UnitQuaternion::new_unchecked(-to_rotation.into_inner());
+            }
+
+            *out = Isometry3::from_parts(
+                from.translation
+                    .vector
+                    .lerp(&to.translation.vector, t)
+                    .into(),
+                from.rotation.slerp(&to_rotation, t),
+            );
+        }
+        out.dirty = true;
+    }
+}
+
+/// Set of morph targets for a skelly rest pose.
+///
+/// Each morph stores offsets of bones from the base skelly
+/// and may be mixed with others with arbitrary weights.
+///
+/// # Example
+///
+/// ```
+/// # use {skelly::{Skelly, Posture, MorphSet}, na::{Point3, Vector3, UnitQuaternion}};
+/// let mut skelly = Skelly::<f32>::new();
+/// let root = skelly.add_root(Point3::origin());
+/// let bone = skelly.attach(Vector3::x(), root);
+///
+/// let mut long = Posture::new(&skelly);
+/// long.set_position(bone, Vector3::x() * 2.0);
+/// let mut bent = Posture::new(&skelly);
+/// bent.set_orientation(root, UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5));
+///
+/// let mut morphs = MorphSet::new(&skelly);
+/// let long = morphs.add_morph(&long);
+/// let bent = morphs.add_morph(&bent);
+///
+/// let mut variant = skelly.clone();
+/// morphs.apply_morphs(&[(long, 1.0)], &mut variant);
+/// assert_eq!(*variant.get_position(bone), Vector3::x() * 2.0);
+///
+/// morphs.apply_morphs(&[(long, 0.5), (bent, 1.0)], &mut variant);
+/// assert!((variant.get_position(bone) - Vector3::x() * 1.5).magnitude() < 1e-5);
+/// assert!((variant.get_orientation(root).angle() - 0.5).abs() < 1e-5);
+/// ```
+pub struct MorphSet<T: Scalar> {
+    base: Vec<Isometry3<T>>,
+    topology: u64,
+    morphs: Vec<Vec<(Vector3<T>, UnitQuaternion<T>)>>,
+}
+
+impl<T> MorphSet<T>
+where
+    T: Scalar,
+{
+    /// Returns new empty morph set for `skelly` rest pose.
+    pub fn new<D>(skelly: &Skelly<T, D>) -> Self
+    where
+        T: RealField,
+    {
+        MorphSet {
+            base: skelly
+                .bones
+                .iter()
+                .map(|bone| bone.isometry.clone())
+                .collect(),
+            topology: skelly.topology_hash(),
+            morphs: Vec::new(),
+        }
+    }
+
+    /// Adds morph which turns base rest pose into `target`.
+    ///
+    /// Returns id of the added morph.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` is not compatible with the base skelly.
+    #[track_caller]
+    pub fn add_morph(&mut self, target: &Posture<T>) -> usize
+    where
+        T: RealField,
+    {
+        assert!(
+            target.topology == self.topology && target.joints.len() == self.base.len(),
+            "Posture is not compatible with the skelly"
+        );
+
+        self.morphs.push(
+            self.base
+                .iter()
+                .zip(&target.joints)
+                .map(|(base, target)| {
+                    (
+                        &target.translation.vector - &base.translation.vector,
+                        base.rotation.inverse() * &target.rotation,
+                    )
+                })
+                .collect(),
+        );
+        self.morphs.len() - 1
+    }
+
+    /// Sets rest pose of `out` skelly to base rest pose
+    /// with morphs mixed according to `weights`.
+    ///
+    /// Each pair in `weights` is morph id and its weight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not compatible with the base skelly
+    /// or morph id is out of bounds.
+    #[track_caller]
+    pub fn apply_morphs<D>(&self, weights: &[(usize, T)], out: &mut Skelly<T, D>)
+    where
+        T: RealField + Copy,
+    {
+        assert!(
+            out.topology_hash() == self.topology && out.bones.len() == self.base.len(),
+            "Skelly is not compatible with the morph set"
+        );
+
+        out.for_each_bone_mut(|index, isometry, _| {
+            *isometry = self.base[index];
+            for &(morph, weight) in weights {
+                let (translation, rotation) = &self.morphs[morph][index];
+                isometry.translation.vector += translation * weight;
+                isometry.rotation *= rotation.powf(weight);
+            }
+        });
+    }
+}
+
+fn write_skinning_matrices<'a, T>(
+    bones: impl Iterator<Item = (&'a Isometry3<T>, Option<usize>)>,
+    skelly_global: &Isometry3<T>,
+    inverse_binds: &[Isometry3<T>],
+    out: &mut [Matrix4<T>],
+) where
+    T: RealField,
+{
+    let len = out.len();
+
+    // Write global matrices first. Parents always precede children.
+    bones
+        .take(len)
+        .enumerate()
+        .for_each(|(index, (isometry, parent))| match parent {
+            Some(parent) => {
+                debug_assert!(parent < index);
+                out[index] = &out[parent] * isometry.to_homogeneous();
+            }
+            None => {
+                out[index] = (skelly_global * isometry).to_homogeneous();
+            }
+        });
+
+    out.iter_mut()
+        .zip(inverse_binds)
+        .for_each(|(matrix, inverse_bind)| *matrix *= inverse_bind.to_homogeneous());
+}